@@ -1,25 +1,68 @@
 // --- constants ---
 pub const DEFAULT_APP: &str = include_str!("../res/app.toml");
 pub const DEFAULT_APP_CLEAN: &str = include_str!("../res/app_clean.toml");
+/// how long to wait between relaunches when an app sets `retries` but no `retry_delay_ms`
+pub const DEFAULT_RETRY_DELAY_MS: u64 = 1000;
+/// keys an app definition understands, at the top level, in `[meta]` and in each `[cmds.<name>]`
+/// (everything else is reported for inline apps by `ran config validate`)
+pub const APP_KEYS: &[&str] = &[
+	"meta", "vars", "env", "working_dir", "log", "background", "retries", "retry_delay_ms", "pre", "post", "cmds",
+];
+pub const META_KEYS: &[&str] = &[
+	"name", "description", "version", "tags", "genre", "developer", "release_year", "playtime_estimate",
+];
+pub const CMD_KEYS: &[&str] = &["bin", "args", "env", "working_dir", "log", "detach_after", "inject", "shell"];
 
 // --- imports ---
 use anyhow::{anyhow, bail, Context, Result};
 use colored::*;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use indexmap::IndexMap as Map;
 use std::fmt::{self, Display, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use toml_edit::{table, value, Array, DocumentMut, Item, Table, Value};
 use walkdir::WalkDir;
+use crate::util::args::{parse_bool, parse_fragment};
 use crate::util::table::*;
 
 // --- functions ---
+/// (private) sets 'key' in 'table', or removes it if there's no 'item'
+fn set_or_remove(table: &mut Table, key: &str, item: Option<Item>) {
+	match item {
+		Some(item) => table[key] = item,
+		None => {
+			table.remove(key);
+		}
+	}
+}
+
+/// (private) parses a whole list given to `app set` (json or inline toml)
+fn parse_list(value: &str, key: &str) -> Result<Vec<String>> {
+	parse_fragment(value).map_err(|e| anyhow!("'{key}' expects an array of strings, e.g. '[\"--a\", \"--b\"]': {e}"))
+}
+
+/// (private) parses a whole table given to `app set` (json or inline toml)
+fn parse_table(value: &str, key: &str) -> Result<Map<String, String>> {
+	parse_fragment(value).map_err(|e| anyhow!("'{key}' expects a table of strings, e.g. '{{ KEY = \"value\" }}': {e}"))
+}
+
+/// normalizes an app name: trimmed, spaces become '_' and backslashes '/', no leading or trailing '/'
 pub fn sanitize_app_name<S: Into<String>>(name: S) -> String {
 	name.into().trim().replace(' ', "_").replace('\\', "/").trim_matches('/').to_string()
 }
 
-pub fn new_app(path: &Path, name: String, clean: bool) -> Result<PathBuf> {
+/// creates {path}/apps/{name}.toml from the built-in template, or from {path}/templates/{template}.toml if given
+pub fn new_app(path: &Path, name: String, clean: bool, template: Option<&str>) -> Result<PathBuf> {
+	let template = template
+		.map(|template| {
+			let file = path.join("templates").join(format!("{}.toml", sanitize_app_name(template)));
+			fs::read_to_string(&file)
+				.map_err(|_| anyhow!("template '{template}' not found (see `ran app template list`)"))
+		})
+		.transpose()?;
+
 	let app_dir = path.join("apps");
 	if !app_dir.exists() {
 		fs::create_dir_all(&app_dir)?;
@@ -31,39 +74,221 @@ pub fn new_app(path: &Path, name: String, clean: bool) -> Result<PathBuf> {
 			path.display()
 		);
 	}
-	fs::write(&path, match clean {
-		true => DEFAULT_APP_CLEAN,
-		false => DEFAULT_APP,
+	fs::write(&path, match (&template, clean) {
+		(Some(template), _) => template.as_str(),
+		(None, true) => DEFAULT_APP_CLEAN,
+		(None, false) => DEFAULT_APP,
 	})?;
 	Ok(path)
 }
 
+/// copies the definition file at 'from' (comments and all) to {path}/templates/{name}.toml
+pub fn save_template(path: &Path, name: String, from: &Path, force: bool) -> Result<PathBuf> {
+	let name = sanitize_app_name(name);
+	if name.is_empty() {
+		bail!("template name cannot be empty");
+	}
+	let dest = path.join("templates").join(format!("{name}.toml"));
+	if dest.exists() && !force {
+		bail!("template already exists: {} (use -f/--force to overwrite it)", dest.display());
+	}
+	if let Some(parent) = dest.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	fs::copy(from, &dest)
+		.map_err(|e| anyhow!("failed to copy file: {e}"))?;
+	Ok(dest)
+}
+
+/// (private) finds all .toml files under 'dir' and returns a map of name -> path, where the name is
+/// the path relative to 'dir' without the extension (e.g. "apps/games/doom.toml" -> "games/doom"),
+/// sorted by name so the order doesn't depend on how the filesystem lists directories
+fn find_toml_files(dir: &Path) -> Map<String, PathBuf> {
+	let mut found = Map::new();
+
+	if !dir.exists() {
+		return found;
+	}
+
+	// walk through the directory
+	for entry in WalkDir::new(dir)
+		.min_depth(1) // don't include the folder itself
+		.into_iter()
+		.filter_map(|e| e.ok())
+	{
+		let path = entry.path();
+
+		// only care about .toml files
+		if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("toml") {
+
+			// sanitize the name/key
+			if let Ok(relative_path) = path.strip_prefix(dir) {
+				let mut name = relative_path.to_string_lossy().to_string();
+
+				// remove .toml extension
+				if name.ends_with(".toml") {
+					name.truncate(name.len() - 5);
+				}
+
+				// normalize slashes and trim
+				let sanitized_name = name
+					.replace('\\', "/") // ensure cross-platform consistency
+					.trim_matches('/')
+					.to_string();
+
+				found.insert(sanitized_name, path.to_path_buf());
+			}
+		}
+	}
+	found.sort_keys();
+	found
+}
+
+/// (private) deserializes args from either an array or a single shell-style string
+/// (e.g. `args = "--foo 'bar baz'"` becomes `["--foo", "bar baz"]`)
+fn deserialize_args<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Args {
+		List(Vec<String>),
+		Line(String),
+	}
+
+	match Args::deserialize(deserializer)? {
+		Args::List(args) => Ok(args),
+		Args::Line(line) => shell_words::split(&line).map_err(serde::de::Error::custom),
+	}
+}
+
 // --- definitions ---
-#[derive(Default, Deserialize, Serialize)]
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct App {
 	pub meta: Option<Meta>,
 	pub vars: Option<Map<String, String>>,
 	pub env: Option<Map<String, String>>,
+	pub working_dir: Option<String>,
+	/// file the app's stdout and stderr are appended to ("null" discards them)
+	pub log: Option<String>,
+	/// launch in the background by default (`-F/--foreground` overrides it)
+	pub background: Option<bool>,
+	/// how often to relaunch a foreground app that exits with an error
+	pub retries: Option<u32>,
+	/// how long to wait between those relaunches (1000ms by default)
+	pub retry_delay_ms: Option<u64>,
+	/// command lines run (in order) before launching any of the app's commands
+	pub pre: Option<Vec<String>>,
+	/// command lines run (in order) after the launched process exits
+	pub post: Option<Vec<String>>,
 	pub cmds: Map<String, Cmd>,
 }
 
-#[derive(Default, Deserialize, Serialize)]
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct Meta {
 	pub name: Option<String>,
 	pub description: Option<String>,
 	pub version: Option<String>,
+	pub tags: Option<Vec<String>>,
+	pub genre: Option<String>,
+	pub developer: Option<String>,
+	pub release_year: Option<u32>,
+	/// free-form, e.g. "40h"
+	pub playtime_estimate: Option<String>,
 }
 
-#[derive(Default, Deserialize, Serialize)]
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct Cmd {
 	pub bin: String,
+	#[serde(default, deserialize_with = "deserialize_args")]
 	pub args: Vec<String>,
 	pub env: Option<Map<String, String>>,
+	pub working_dir: Option<String>,
+	pub log: Option<String>,
+	pub detach_after: Option<u64>,
+	pub inject: Option<Inject>,
+	/// run bin and args as one command line through the system shell (`sh -c` / `cmd /C`)
+	pub shell: Option<bool>,
+}
+
+/// an app shown together with a summary line about its definition (see `ran app info`)
+pub struct AppInfo<'a> {
+	pub app: &'a App,
+	pub footer: String,
+	/// the final environment of a launch, shown in its own table (see `ran app info --resolved`)
+	pub resolved_env: Option<Map<String, String>>,
+}
+
+/// how a command's args are combined with the args of its runner app (`bin = "@runner"`)
+#[derive(Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Inject {
+	/// in place of the runner's `%!`, or appended if it has none
+	#[default]
+	Auto,
+	/// before the runner's args (the runner's `%!` is dropped)
+	Prepend,
+	/// after the runner's args (the runner's `%!` is dropped)
+	Append,
+	/// instead of the runner's args
+	Replace,
 }
 
 // --- implementations ---
+impl Display for Inject {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Inject::Auto => "auto",
+			Inject::Prepend => "prepend",
+			Inject::Append => "append",
+			Inject::Replace => "replace",
+		})
+	}
+}
+
+impl FromStr for Inject {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		match s.trim().to_lowercase().as_str() {
+			"auto" => Ok(Inject::Auto),
+			"prepend" => Ok(Inject::Prepend),
+			"append" => Ok(Inject::Append),
+			"replace" => Ok(Inject::Replace),
+			_ => bail!("parse error: '{s}' is not one of auto, prepend, append, replace"),
+		}
+	}
+}
+
 impl Display for App {
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		self.render(f, None)
+	}
+}
+
+impl Display for AppInfo<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		self.app.render(f, Some(&self.footer))?;
+
+		if let Some(env) = &self.resolved_env {
+			let mut env_map = Map::new();
+			let mut vars: Vec<_> = env.iter().collect();
+			vars.sort();
+			for (name, value) in vars {
+				env_map.insert(format!("${name}").bright_blue().to_string(), value.clone());
+			}
+			let mut sections = Map::new();
+			sections.insert(format!("{}", "launch".bright_blue().bold()), env_map);
+			make_table(f, "Resolved Environment", generate_rows(sections))?;
+		}
+		Ok(())
+	}
+}
+
+impl App {
+	/// (private) draws the app info and commands tables, with optional last lines in the app info table
+	fn render(&self, f: &mut Formatter<'_>, footer: Option<&str>) -> fmt::Result {
 		let mut sections: Map<String, Map<String, String>> = Map::new();
 
 		// 1. metadata
@@ -78,11 +303,51 @@ impl Display for App {
 			if let Some(desc) = &meta.description {
 				meta_map.insert("Description".bright_yellow().to_string(), desc.into());
 			}
+			if let Some(tags) = meta.tags.as_ref().filter(|t| !t.is_empty()) {
+				meta_map.insert("Tags".bright_yellow().to_string(), tags.join(", "));
+			}
+			if let Some(genre) = &meta.genre {
+				meta_map.insert("Genre".bright_yellow().to_string(), genre.into());
+			}
+			if let Some(developer) = &meta.developer {
+				meta_map.insert("Developer".bright_yellow().to_string(), developer.into());
+			}
+			if let Some(year) = meta.release_year {
+				meta_map.insert("Release Year".bright_yellow().to_string(), year.to_string());
+			}
+			if let Some(playtime) = &meta.playtime_estimate {
+				meta_map.insert("Playtime".bright_yellow().to_string(), playtime.into());
+			}
 
 			sections.insert(format!("{}", "Metadata".bright_yellow().bold()), meta_map);
 		}
 
-		// 2. local vars
+		// 2. general settings
+		let mut general = Map::new();
+		if let Some(dir) = &self.working_dir {
+			general.insert("Working Directory".bright_cyan().to_string(), dir.clone());
+		}
+		if let Some(log) = &self.log {
+			general.insert("Log File".bright_cyan().to_string(), log.clone());
+		}
+		if let Some(background) = self.background {
+			general.insert("Background".bright_cyan().to_string(), background.to_string());
+		}
+		if let Some(retries) = self.retries {
+			let delay = self.retry_delay_ms.unwrap_or(DEFAULT_RETRY_DELAY_MS);
+			general.insert("Retries".bright_cyan().to_string(), format!("{retries} ({delay}ms apart)"));
+		}
+		if let Some(pre) = self.pre.as_ref().filter(|p| !p.is_empty()) {
+			general.insert("Pre Hooks".bright_cyan().to_string(), pre.join("\n"));
+		}
+		if let Some(post) = self.post.as_ref().filter(|p| !p.is_empty()) {
+			general.insert("Post Hooks".bright_cyan().to_string(), post.join("\n"));
+		}
+		if !general.is_empty() {
+			sections.insert(format!("{}", "General Settings".bright_cyan().bold()), general);
+		}
+
+		// 3. local vars
 		let mut vars_map = Map::new();
 		if let Some(vars) = &self.vars {
 			if vars.is_empty() {
@@ -96,7 +361,7 @@ impl Display for App {
 			sections.insert(format!("{}", "Local Variables".bright_red().bold()), vars_map);
 		}
 
-		// 3. local environment
+		// 4. local environment
 		let mut env_map = Map::new();
 		if let Some(env) = &self.env {
 			if env.is_empty() {
@@ -111,7 +376,10 @@ impl Display for App {
 		}
 
 		// 5. generate main table
-		let rows = generate_rows(sections);
+		let mut rows = generate_rows(sections);
+		if let Some(footer) = footer {
+			rows.extend(footer.lines().map(|line| line.bright_black().to_string()));
+		}
 		make_table(f, "App Info", rows)?;
 
 		// 6. commands
@@ -127,6 +395,21 @@ impl Display for App {
 				"Arguments".bright_green().to_string(),
 				shell_words::join(&cmd.args)
 			);
+			if let Some(dir) = &cmd.working_dir {
+				cmd_map.insert("Working Directory".bright_green().to_string(), dir.clone());
+			}
+			if let Some(log) = &cmd.log {
+				cmd_map.insert("Log File".bright_green().to_string(), log.clone());
+			}
+			if let Some(secs) = cmd.detach_after {
+				cmd_map.insert("Detach After".bright_green().to_string(), format!("{secs}s"));
+			}
+			if let Some(inject) = cmd.inject {
+				cmd_map.insert("Inject Args".bright_green().to_string(), inject.to_string());
+			}
+			if let Some(shell) = cmd.shell {
+				cmd_map.insert("Shell".bright_green().to_string(), shell.to_string());
+			}
 			cmd_sections.insert(format!("{}", name.bright_green().bold()), cmd_map);
 
 			let mut env_map = Map::new();
@@ -174,12 +457,16 @@ impl App {
 			if !doc.as_table().contains_key("meta") {
 				doc["meta"] = table();
 			}
+			// only the known fields are rewritten, anything else in the table is kept
 			let table = doc["meta"].as_table_mut().unwrap();
-			table.clear();
-
-			if let Some(name) = &meta.name { table["name"] = value(name.to_string()); }
-			if let Some(desc) = &meta.description { table["description"] = value(desc.to_string()); }
-			if let Some(ver) = &meta.version { table["version"] = value(ver.to_string()); }
+			set_or_remove(table, "name", meta.name.as_ref().map(|name| value(name.clone())));
+			set_or_remove(table, "description", meta.description.as_ref().map(|desc| value(desc.clone())));
+			set_or_remove(table, "version", meta.version.as_ref().map(|ver| value(ver.clone())));
+			set_or_remove(table, "tags", meta.tags.as_ref().map(|tags| value(tags.iter().collect::<Array>())));
+			set_or_remove(table, "genre", meta.genre.as_ref().map(|genre| value(genre.clone())));
+			set_or_remove(table, "developer", meta.developer.as_ref().map(|dev| value(dev.clone())));
+			set_or_remove(table, "release_year", meta.release_year.map(|year| value(year as i64)));
+			set_or_remove(table, "playtime_estimate", meta.playtime_estimate.as_ref().map(|time| value(time.clone())));
 		} else {
 			doc.as_table_mut().remove("meta");
 		}
@@ -208,84 +495,100 @@ impl App {
 			doc.as_table_mut().remove("env");
 		}
 
-		// 4. cmds
+		// 4. working directory
+		if let Some(dir) = &self.working_dir {
+			doc["working_dir"] = value(dir.clone());
+		} else {
+			doc.as_table_mut().remove("working_dir");
+		}
+		if let Some(log) = &self.log {
+			doc["log"] = value(log.clone());
+		} else {
+			doc.as_table_mut().remove("log");
+		}
+		if let Some(background) = self.background {
+			doc["background"] = value(background);
+		} else {
+			doc.as_table_mut().remove("background");
+		}
+		if let Some(retries) = self.retries {
+			doc["retries"] = value(retries as i64);
+		} else {
+			doc.as_table_mut().remove("retries");
+		}
+		if let Some(delay) = self.retry_delay_ms {
+			doc["retry_delay_ms"] = value(delay as i64);
+		} else {
+			doc.as_table_mut().remove("retry_delay_ms");
+		}
+		for (key, hooks) in [("pre", &self.pre), ("post", &self.post)] {
+			if let Some(hooks) = hooks {
+				let mut arr = Array::new();
+				for hook in hooks {
+					arr.push(hook.clone());
+				}
+				doc[key] = Item::Value(Value::Array(arr));
+			} else {
+				doc.as_table_mut().remove(key);
+			}
+		}
+
+		// 5. cmds
 		if !self.cmds.is_empty() {
 			if !doc.as_table().contains_key("cmds") {
 				doc["cmds"] = table();
 			}
 			let table = doc["cmds"].as_table_mut().unwrap();
-			table.clear();
+			// removed commands go, the others are updated in place so unknown keys survive
+			table.retain(|name, _| self.cmds.contains_key(name));
 
 			for (cmd_name, cmd) in &self.cmds {
-				let mut cmd_table = Table::new();
-				cmd_table["bin"] = value(cmd.bin.clone());
-				if !cmd.args.is_empty() {
-					let mut arr = Array::new();
-					for item in &cmd.args {
-						arr.push(item.clone());
-					}
-					cmd_table["args"] = Item::Value(Value::Array(arr));
+				if !table.get(cmd_name).is_some_and(Item::is_table) {
+					table[cmd_name] = Item::Table(Table::new());
 				}
-				if let Some(env) = &cmd.env {
+				let cmd_table = table[cmd_name].as_table_mut().unwrap();
+				cmd_table["bin"] = value(cmd.bin.clone());
+				let args = (!cmd.args.is_empty()).then(|| value(cmd.args.iter().collect::<Array>()));
+				set_or_remove(cmd_table, "args", args);
+				set_or_remove(cmd_table, "working_dir", cmd.working_dir.as_ref().map(|dir| value(dir.clone())));
+				set_or_remove(cmd_table, "log", cmd.log.as_ref().map(|log| value(log.clone())));
+				set_or_remove(cmd_table, "detach_after", cmd.detach_after.map(|secs| value(secs as i64)));
+				set_or_remove(cmd_table, "inject", cmd.inject.map(|inject| value(inject.to_string())));
+				set_or_remove(cmd_table, "shell", cmd.shell.map(value));
+				let env = cmd.env.as_ref().map(|env| {
 					let mut env_table = Table::new();
 					for (k, v) in env { env_table[k] = value(v.clone()); }
-					cmd_table["env"] = Item::Table(env_table);
-				}
-				table[cmd_name] = Item::Table(cmd_table);
+					Item::Table(env_table)
+				});
+				set_or_remove(cmd_table, "env", env);
 			}
 		} else {
 			doc.as_table_mut().remove("cmds");
 		}
 
-		// 5. write back
+		// 6. write back
 		fs::write(app_file, doc.to_string())
 			.with_context(|| format!("failed to write app to {:?}", app_file.display()))?;
 
 		Ok(())
 	}
 
-	/// finds all app definitions in {config_path}/apps and returns a map of app name -> path to definition
+	/// finds all app definitions in {config_path}/apps and returns a map of app name -> path to definition,
+	/// sorted by name
 	pub fn find_all(config_path: &Path) -> Map<String, PathBuf> {
-		let mut apps = Map::new();
-		let apps_dir = config_path.join("apps");
-
-		if !apps_dir.exists() {
-			return apps;
-		}
-
-		// walk through the apps directory
-		for entry in WalkDir::new(apps_dir)
-			.min_depth(1) // don't include the apps folder itself
-			.into_iter()
-			.filter_map(|e| e.ok())
-		{
-			let path = entry.path();
-
-			// only care about .toml files
-			if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("toml") {
-
-				// sanitize the name/key
-				// we want the path relative to the "apps" folder, without the .toml
-				// e.g., "apps/games/doom.toml" -> "games/doom"
-				if let Ok(relative_path) = path.strip_prefix(&config_path.join("apps")) {
-					let mut name = relative_path.to_string_lossy().to_string();
-
-					// remove .toml extension
-					if name.ends_with(".toml") {
-						name.truncate(name.len() - 5);
-					}
+		find_toml_files(&config_path.join("apps"))
+	}
 
-					// normalize slashes and trim
-					let sanitized_name = name
-						.replace('\\', "/") // ensure cross-platform consistency
-						.trim_matches('/')
-						.to_string();
+	/// whether the app's metadata has 'tag' (case-insensitive)
+	pub fn has_tag(&self, tag: &str) -> bool {
+		self.meta.as_ref()
+			.and_then(|meta| meta.tags.as_ref())
+			.is_some_and(|tags| tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+	}
 
-					apps.insert(sanitized_name, path.to_path_buf());
-				}
-			}
-		}
-		apps
+	/// finds all app templates in {config_path}/templates and returns a map of template name -> path
+	pub fn find_templates(config_path: &Path) -> Map<String, PathBuf> {
+		find_toml_files(&config_path.join("templates"))
 	}
 
 	// getters and setters
@@ -310,14 +613,17 @@ impl App {
 		parts: &[&str]
 	) -> Option<String> {
 		match parts {
-			["*"] => {
-				return Some(format!("{}", self));
-			}
+			["*"] => Some(format!("{}", self)),
 			["cmds", cmd, rest @ ..] => {
 				let cmd = self.cmds.get(*cmd)?;
 
 				match rest {
 					["bin"] => Some(cmd.bin.clone()),
+					["working_dir"] => cmd.working_dir.clone(),
+					["log"] => cmd.log.clone(),
+					["detach_after"] => cmd.detach_after.map(|s| s.to_string()),
+					["inject"] => Some(cmd.inject.unwrap_or_default().to_string()),
+					["shell"] => cmd.shell.map(|shell| shell.to_string()),
 					["env", k] => cmd.env.as_ref()?.get(*k).cloned(),
 					["args", num] => match *num {
 						"*" => Some(
@@ -337,11 +643,28 @@ impl App {
 					"name" => meta.name.clone(),
 					"description" => meta.description.clone(),
 					"version" => meta.version.clone(),
+					"tags" => meta.tags.as_ref().map(|tags| tags.join(",")),
+					"genre" => meta.genre.clone(),
+					"developer" => meta.developer.clone(),
+					"release_year" => meta.release_year.map(|year| year.to_string()),
+					"playtime_estimate" => meta.playtime_estimate.clone(),
 					_ => None,
 				}
 			}
 			["vars", k] => self.vars.as_ref()?.get(*k).cloned(),
 			["env", k] => self.env.as_ref()?.get(*k).cloned(),
+			["working_dir"] => self.working_dir.clone(),
+			["log"] => self.log.clone(),
+			["background"] => self.background.map(|b| b.to_string()),
+			["retries"] => self.retries.map(|n| n.to_string()),
+			["retry_delay_ms"] => self.retry_delay_ms.map(|ms| ms.to_string()),
+			[hook @ ("pre" | "post"), num] => {
+				let hooks = if *hook == "pre" { self.pre.as_ref()? } else { self.post.as_ref()? };
+				match *num {
+					"*" => Some(hooks.join("\n")),
+					_ => hooks.get(num.parse::<usize>().ok()?).cloned(),
+				}
+			}
 			_ => None,
 		}
 	}
@@ -362,11 +685,42 @@ impl App {
 						cmd.bin = value;
 						Ok(())
 					}
+					["working_dir"] => {
+						cmd.working_dir = Some(value);
+						Ok(())
+					}
+					["log"] => {
+						cmd.log = Some(value);
+						Ok(())
+					}
+					["detach_after"] => {
+						cmd.detach_after = Some(value.trim().parse()
+							.map_err(|_| anyhow!("parse error: '{value}' is not a number of seconds"))?);
+						Ok(())
+					}
+					["inject"] => {
+						cmd.inject = Some(value.parse()?);
+						Ok(())
+					}
+					["shell"] => {
+						cmd.shell = Some(parse_bool(&value)
+							.ok_or(anyhow!("parse error: '{value}' is not a boolean"))?);
+						Ok(())
+					}
 					["env", k] => {
 						let env = cmd.env.get_or_insert_default();
 						env.insert((*k).to_string(), value);
 						Ok(())
 					}
+					// whole lists and tables, like `config set` takes them
+					["args"] => {
+						cmd.args = parse_list(&value, "args")?;
+						Ok(())
+					}
+					["env"] => {
+						cmd.env = Some(parse_table(&value, "env")?);
+						Ok(())
+					}
 					["args", num] => match *num {
 						"*" => {
 							cmd.args = shell_words::split(&value)
@@ -395,6 +749,15 @@ impl App {
 					"name" => meta.name = Some(value),
 					"description" => meta.description = Some(value),
 					"version" => meta.version = Some(value),
+					// comma-separated, e.g. "multiplayer,emulated"
+					"tags" => meta.tags = Some(
+						value.split(',').map(str::trim).filter(|t| !t.is_empty()).map(String::from).collect()
+					),
+					"genre" => meta.genre = Some(value),
+					"developer" => meta.developer = Some(value),
+					"release_year" => meta.release_year = Some(value.trim().parse()
+						.map_err(|_| anyhow!("parse error: '{value}' is not a year"))?),
+					"playtime_estimate" => meta.playtime_estimate = Some(value),
 					_ => return Err(anyhow!("invalid meta field '{}'", field)),
 				}
 
@@ -410,6 +773,55 @@ impl App {
 				env.insert((*k).to_string(), value);
 				Ok(())
 			}
+			["vars"] => {
+				self.vars = Some(parse_table(&value, "vars")?);
+				Ok(())
+			}
+			["env"] => {
+				self.env = Some(parse_table(&value, "env")?);
+				Ok(())
+			}
+			[hook @ ("pre" | "post")] => {
+				let hooks = Some(parse_list(&value, hook)?);
+				if *hook == "pre" { self.pre = hooks } else { self.post = hooks }
+				Ok(())
+			}
+			["working_dir"] => {
+				self.working_dir = Some(value);
+				Ok(())
+			}
+			["log"] => {
+				self.log = Some(value);
+				Ok(())
+			}
+			["retries"] => {
+				self.retries = Some(value.trim().parse()
+					.map_err(|_| anyhow!("parse error: '{value}' is not a positive integer"))?);
+				Ok(())
+			}
+			["retry_delay_ms"] => {
+				self.retry_delay_ms = Some(value.trim().parse()
+					.map_err(|_| anyhow!("parse error: '{value}' is not a positive integer"))?);
+				Ok(())
+			}
+			["background"] => {
+				self.background = Some(parse_bool(&value)
+					.ok_or(anyhow!("parse error: '{value}' is not a boolean"))?);
+				Ok(())
+			}
+			[hook @ ("pre" | "post"), num] => {
+				let hooks = if *hook == "pre" { &mut self.pre } else { &mut self.post };
+				let hooks = hooks.get_or_insert_default();
+				let index = num.parse::<usize>()
+					.map_err(|_| anyhow!("invalid hook index '{}'", num))?;
+				// setting the index right after the last hook adds a new one
+				match index.cmp(&hooks.len()) {
+					std::cmp::Ordering::Less => hooks[index] = value,
+					std::cmp::Ordering::Equal => hooks.push(value),
+					std::cmp::Ordering::Greater => bail!("hook index {} out of bounds", index),
+				}
+				Ok(())
+			}
 			_ => Err(anyhow!("invalid path")),
 		}
 	}
@@ -433,6 +845,26 @@ impl App {
 						cmd.bin.clear();
 						Ok(())
 					}
+					["working_dir"] => {
+						cmd.working_dir = None;
+						Ok(())
+					}
+					["log"] => {
+						cmd.log = None;
+						Ok(())
+					}
+					["detach_after"] => {
+						cmd.detach_after = None;
+						Ok(())
+					}
+					["inject"] => {
+						cmd.inject = None;
+						Ok(())
+					}
+					["shell"] => {
+						cmd.shell = None;
+						Ok(())
+					}
 					["env", k] => {
 						match *k {
 							"*" => {
@@ -479,6 +911,11 @@ impl App {
 					"name" => meta.name = None,
 					"description" => meta.description = None,
 					"version" => meta.version = None,
+					"tags" => meta.tags = None,
+					"genre" => meta.genre = None,
+					"developer" => meta.developer = None,
+					"release_year" => meta.release_year = None,
+					"playtime_estimate" => meta.playtime_estimate = None,
 					_ => return Err(anyhow!("invalid meta field '{}'", field)),
 				}
 
@@ -512,7 +949,129 @@ impl App {
 				}
 				Ok(())
 			}
+			["working_dir"] => {
+				self.working_dir = None;
+				Ok(())
+			}
+			["log"] => {
+				self.log = None;
+				Ok(())
+			}
+			["retries"] => {
+				self.retries = None;
+				Ok(())
+			}
+			["retry_delay_ms"] => {
+				self.retry_delay_ms = None;
+				Ok(())
+			}
+			["background"] => {
+				self.background = None;
+				Ok(())
+			}
+			[hook @ ("pre" | "post"), num] => {
+				let hooks = if *hook == "pre" { &mut self.pre } else { &mut self.post };
+				match *num {
+					"*" => *hooks = None,
+					_ => {
+						let index = num.parse::<usize>()
+							.map_err(|_| anyhow!("invalid hook index '{}'", num))?;
+						let hooks = hooks
+							.as_mut()
+							.ok_or_else(|| anyhow!("{hook} hooks not initialized"))?;
+						if index >= hooks.len() {
+							bail!("hook index {} out of bounds", index);
+						}
+						hooks.remove(index);
+					}
+				}
+				Ok(())
+			}
 			_ => Err(anyhow!("invalid path")),
 		}
 	}
 }
+
+// --- tests ---
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// the 'launch' command's args from an app definition
+	fn launch_args(toml: &str) -> Vec<String> {
+		let app: App = toml::from_str(toml).unwrap();
+		app.cmds["launch"].args.clone()
+	}
+
+	#[test]
+	fn args_from_an_array() {
+		let args = launch_args("[cmds.launch]\nbin = \"game\"\nargs = [\"-w\", \"two words\"]\n");
+		assert_eq!(args, ["-w", "two words"]);
+	}
+
+	#[test]
+	fn args_from_a_quoted_string() {
+		let args = launch_args("[cmds.launch]\nbin = \"game\"\nargs = \"-w 'two words' \\\"and three more\\\" %!\"\n");
+		assert_eq!(args, ["-w", "two words", "and three more", "%!"]);
+	}
+
+	#[test]
+	fn args_default_to_empty() {
+		assert!(launch_args("[cmds.launch]\nbin = \"game\"\n").is_empty());
+	}
+
+	#[test]
+	fn args_with_unclosed_quotes_are_rejected() {
+		assert!(toml::from_str::<App>("[cmds.launch]\nbin = \"game\"\nargs = \"-w 'unclosed\"\n").is_err());
+	}
+
+	#[test]
+	fn key_lists_match_the_fields() {
+		// no `..Default::default()`, so a new field doesn't compile until it's added here (and to the lists)
+		let text = || Some(String::new());
+		let cmd = Cmd {
+			bin: String::new(),
+			args: Vec::new(),
+			env: Some(Map::new()),
+			working_dir: text(),
+			log: text(),
+			detach_after: Some(0),
+			inject: Some(Inject::Auto),
+			shell: Some(false),
+		};
+		let meta = Meta {
+			name: text(),
+			description: text(),
+			version: text(),
+			tags: Some(Vec::new()),
+			genre: text(),
+			developer: text(),
+			release_year: Some(0),
+			playtime_estimate: text(),
+		};
+		let app = App {
+			meta: Some(meta.clone()),
+			vars: Some(Map::new()),
+			env: Some(Map::new()),
+			working_dir: text(),
+			log: text(),
+			background: Some(false),
+			retries: Some(0),
+			retry_delay_ms: Some(0),
+			pre: Some(Vec::new()),
+			post: Some(Vec::new()),
+			cmds: Map::new(),
+		};
+
+		// both sorted, toml's tables are
+		let keys = |value: toml::Value| value.as_table().unwrap().keys().cloned().collect::<Vec<_>>();
+		let sorted = |known: &[&'static str]| {
+			let mut known = known.to_vec();
+			known.sort();
+			known
+		};
+		assert_eq!(keys(toml::Value::try_from(app).unwrap()), sorted(APP_KEYS));
+		assert_eq!(keys(toml::Value::try_from(meta).unwrap()), sorted(META_KEYS));
+		assert_eq!(keys(toml::Value::try_from(cmd).unwrap()), sorted(CMD_KEYS));
+	}
+}
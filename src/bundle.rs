@@ -0,0 +1,111 @@
+// --- imports ---
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use ran_launcher::app::App;
+
+// --- definitions ---
+/// a portable backup of a config directory (config.toml and every app definition), stored as one
+/// toml document with a `[[file]]` entry per file (see `ran export`/`ran import`)
+#[derive(Deserialize, Serialize)]
+pub struct Bundle {
+	#[serde(rename = "file", default)]
+	pub files: Vec<BundleFile>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BundleFile {
+	/// path relative to the config directory, always with '/' separators (e.g. "apps/games/doom.toml")
+	pub path: String,
+	pub content: String,
+}
+
+// --- implementations ---
+impl Bundle {
+	/// collects 'config_file' (stored as config.toml) and all apps/**/*.toml from 'config_path'
+	pub fn export(config_path: &Path, config_file: &Path) -> Result<Self> {
+		let mut files = Vec::new();
+
+		if config_file.exists() {
+			files.push(BundleFile {
+				path: "config.toml".into(),
+				content: fs::read_to_string(config_file)
+					.with_context(|| format!("failed to read '{}'", config_file.display()))?,
+			});
+		}
+
+		let apps_dir = config_path.join("apps");
+		for path in App::find_all(config_path).values() {
+			let relative = path.strip_prefix(&apps_dir).unwrap_or(path);
+			let relative: Vec<String> = relative.components()
+				.map(|c| c.as_os_str().to_string_lossy().into_owned())
+				.collect();
+			files.push(BundleFile {
+				path: format!("apps/{}", relative.join("/")),
+				content: fs::read_to_string(path)
+					.with_context(|| format!("failed to read '{}'", path.display()))?,
+			});
+		}
+
+		Ok(Self { files })
+	}
+
+	pub fn parse(text: &str) -> Result<Self> {
+		toml::from_str(text).map_err(|e| anyhow!("invalid export file: {}", e.message()))
+	}
+
+	pub fn to_toml(&self) -> Result<String> {
+		Ok(format!("# ran export, restore it with `ran import <file>`\n\n{}", toml::to_string(self)?))
+	}
+
+	/// writes every file into 'config_path' (config.toml goes to 'config_file'), returning the written
+	/// paths. nothing is written if a path is invalid, or if a file already exists and 'force' is not set.
+	pub fn import(&self, config_path: &Path, config_file: &Path, force: bool) -> Result<Vec<PathBuf>> {
+		// check everything first, so a bad bundle doesn't leave a half-imported setup behind
+		let mut targets = Vec::new();
+		for file in &self.files {
+			let relative = bundle_path(&file.path)?;
+			let target = if file.path == "config.toml" { config_file.to_path_buf() } else { config_path.join(relative) };
+			if target.exists() && !force {
+				bail!("'{}' already exists (use -f/--force to overwrite)", target.display());
+			}
+			targets.push(target);
+		}
+
+		for (file, target) in self.files.iter().zip(&targets) {
+			if let Some(parent) = target.parent() {
+				fs::create_dir_all(parent)?;
+			}
+			fs::write(target, &file.content)
+				.with_context(|| format!("failed to write '{}'", target.display()))?;
+		}
+		Ok(targets)
+	}
+}
+
+// --- functions ---
+/// checks that 'signature_file' is a minisign signature of 'data' made with the secret key
+/// belonging to the public key in 'key_file'
+#[cfg(feature = "verify")]
+pub fn verify_signature(data: &[u8], key_file: &Path, signature_file: &Path) -> Result<()> {
+	use minisign_verify::{PublicKey, Signature};
+
+	let key = PublicKey::from_file(key_file)
+		.map_err(|e| anyhow!("failed to read public key '{}': {e}", key_file.display()))?;
+	let signature = Signature::from_file(signature_file)
+		.map_err(|e| anyhow!("failed to read signature '{}': {e}", signature_file.display()))?;
+	key.verify(data, &signature, false)
+		.map_err(|e| anyhow!("signature '{}' doesn't match, not importing: {e}", signature_file.display()))
+}
+
+/// (private) checks a bundle path, which must be config.toml or a .toml file under apps/
+fn bundle_path(path: &str) -> Result<PathBuf> {
+	let relative = PathBuf::from(path);
+	let plain = relative.components().all(|c| matches!(c, Component::Normal(_)));
+	let is_app = relative.starts_with("apps") && relative.extension().is_some_and(|ext| ext == "toml");
+	if !plain || !(path == "config.toml" || is_app) {
+		bail!("invalid path '{path}' in export file");
+	}
+	Ok(relative)
+}
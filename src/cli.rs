@@ -1,6 +1,15 @@
+// --- constants ---
+/// the environment variable that makes ran complete a command line instead of running it
+/// (`COMPLETE=bash ran`, see `ran completions`)
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
 // --- imports ---
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::{ArgValueCandidates, CompletionCandidate, Shell};
 use std::path::PathBuf;
+use ran_launcher::util::args::parse_env_pair;
+use ran_launcher::util::fs::default_config_path;
+use ran_launcher::{LaunchOpts, Launcher};
 
 // --- definitions ---
 /// ran - run anything now
@@ -11,6 +20,7 @@ use std::path::PathBuf;
 /// licensed under apache 2.0.
 #[derive(Parser)]
 #[command(
+	name = "ran",
 	author,
 	version,
 	about,
@@ -26,6 +36,27 @@ pub struct Cli {
 	)]
 	pub config: Option<PathBuf>,
 
+	#[arg(
+		long,
+		env = "RANCFG_FILE",
+		value_name = "FILE",
+		help = "path for config.toml (defaults to config.toml in the --config directory)",
+		long_help = "path for config.toml. only the config file moves: apps/, templates and the launch history stay in the --config directory (or its default). defaults to config.toml in that directory.",
+	)]
+	pub config_file: Option<PathBuf>,
+
+	/// print machine-readable json instead of pretty output (app list/info, config get)
+	#[arg(long, global = true)]
+	pub json: bool,
+
+	/// disable colored output (also disabled by $NO_COLOR, or when output isn't a terminal)
+	#[arg(long, global = true)]
+	pub no_color: bool,
+
+	/// log how a launch is resolved to stderr (-vv also logs every variable expansion)
+	#[arg(short, long, global = true, action = clap::ArgAction::Count)]
+	pub verbose: u8,
+
 	#[command(subcommand)]
 	pub cmd: Option<Command>,
 }
@@ -34,26 +65,48 @@ pub struct Cli {
 pub enum Command {
 	/// launches an app with the 'launch' command
 	Launch {
-		/// app to be launched
-		name: String,
-		/// arguments passed to the app
-		args: Vec<String>,
-		/// run the command in the background
-		#[arg(short, long)]
-		background: bool,
+		#[command(flatten)]
+		opts: LaunchArgs,
+		/// app to be launched, followed by the arguments passed to it as-is (everything after the app
+		/// name, including `--` and ones starting with '-'). ran's own options go before the app name
+		#[arg(
+			value_names = ["APP", "ARGS"],
+			required = true,
+			trailing_var_arg = true,
+			allow_hyphen_values = true,
+			add = ArgValueCandidates::new(complete_app_names),
+		)]
+		app: Vec<String>,
 	},
 
 	/// launches a specific command of an app
 	Cmd {
+		#[command(flatten)]
+		opts: LaunchArgs,
 		/// command to run
 		cmd: String,
-		/// app to be launched
-		name: String,
-		/// arguments passed to the command
-		args: Vec<String>,
-		/// run the command in the background
-		#[arg(short, long)]
-		background: bool,
+		/// app to be launched, followed by the arguments passed to the command as-is (everything after
+		/// the app name, including `--` and ones starting with '-'). ran's own options go before the app name
+		#[arg(
+			value_names = ["APP", "ARGS"],
+			required = true,
+			trailing_var_arg = true,
+			allow_hyphen_values = true,
+			add = ArgValueCandidates::new(complete_app_names),
+		)]
+		app: Vec<String>,
+	},
+
+	/// launches several apps one after another (each with its 'launch' command)
+	Group {
+		/// apps to be launched, in order
+		#[arg(required = true, add = ArgValueCandidates::new(complete_app_names))]
+		names: Vec<String>,
+		/// resolve every app before launching any of them, and launch nothing if one fails
+		#[arg(long)]
+		atomic: bool,
+		#[command(flatten)]
+		opts: LaunchArgs,
 	},
 
 	/// application management subcommands
@@ -71,29 +124,229 @@ pub enum Command {
 	/// global variables management
 	#[command(subcommand)]
 	Var(VarCmd),
+
+	/// parses every app definition and reports the ones that fail (exits non-zero if any do)
+	ValidateAll,
+
+	/// shows an overview of your apps (count, aliases, folders) and your most launched apps
+	Stats,
+
+	/// prints the path of the definition file an app name or alias resolves to
+	Which {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
+		app: String,
+	},
+
+	/// bundles config.toml and all app definitions into one file (for backups or moving machines)
+	Export {
+		/// file to write to (prints to stdout if not given)
+		out: Option<PathBuf>,
+	},
+
+	/// restores a file made by `ran export` into the config directory
+	Import {
+		file: PathBuf,
+		/// overwrite files that already exist
+		#[arg(short, long)]
+		force: bool,
+		/// refuse to import unless the file's minisign signature (<file>.minisig) was made with the
+		/// secret key belonging to this public key file
+		#[cfg(feature = "verify")]
+		#[arg(long, value_name = "KEYFILE")]
+		verify: Option<PathBuf>,
+	},
+
+	/// lists recently launched apps
+	History {
+		/// how many of the most recent launches to show (shows all by default)
+		#[arg(short = 'n', long)]
+		limit: Option<usize>,
+		/// print the launches as csv (timestamp, app, args, background, exit_code) for spreadsheets
+		/// and other tools. `--json` prints every recorded field
+		#[arg(long, conflicts_with = "json")]
+		csv: bool,
+	},
+
+	/// checks your setup for common problems (and optionally repairs them)
+	Doctor {
+		/// offer to repair the problems that were found
+		#[arg(long)]
+		fix: bool,
+		/// apply fixes without asking
+		#[arg(short, long, requires = "fix")]
+		yes: bool,
+	},
+
+	/// prints a shell completion script (e.g. `ran completions zsh > _ran`). app names are completed
+	/// by calling back into ran, so the script keeps up with new apps
+	Completions {
+		/// shell to generate completions for
+		shell: Shell,
+	},
+
+	/// prints all app names (app files and inline apps), one per line
+	#[command(name = "__complete_apps", hide = true)]
+	CompleteApps,
+}
+
+/// the orders `app list --sort` knows
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum ListSort {
+	#[default]
+	Name,
+	Path,
+	Launches,
+}
+
+/// the outputs `app list --format` knows
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum ListFormat {
+	/// `name -- path`, one app per line
+	#[default]
+	Line,
+	/// just the names, one per line (for piping)
+	Names,
+	/// a json array of names and paths, same as `--json`
+	Json,
+}
+
+/// options shared by `launch`, `cmd` and `group`
+#[derive(Args, Default)]
+pub struct LaunchArgs {
+	/// run the command in the background (in a new terminal if `terminal_runner` is set in the config),
+	/// detached from ran's terminal so it keeps running when that is closed
+	#[arg(short, long)]
+	pub background: bool,
+
+	/// run the command in the foreground, even if the app has `background = true`
+	#[arg(short = 'F', long, conflicts_with = "background")]
+	pub foreground: bool,
+
+	#[arg(
+		long,
+		value_name = "FILE",
+		help = "env file (KEY=VALUE per line) layered between global and app env (defaults to config_path/launch.env)",
+	)]
+	pub user_env: Option<PathBuf>,
+
+	/// set an environment variable for this launch only, overriding every other env (repeatable)
+	#[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = parse_env_pair)]
+	pub env: Vec<(String, String)>,
+
+	/// don't inherit ran's environment, the app (and its hooks) only get the env ran layers on top
+	/// (config, user env file, app and `--env`). variables like PATH or HOME may have to be set explicitly
+	#[arg(long, alias = "no-env-inherit")]
+	pub clean_env: bool,
+
+	/// run the app in this directory instead of its `working_dir`
+	#[arg(long, value_name = "DIR")]
+	pub cwd: Option<PathBuf>,
+
+	/// read additional whitespace/newline-separated arguments from stdin (appended after positional args)
+	#[arg(long)]
+	pub args_from_stdin: bool,
+
+	/// don't print ran's own status lines ("launching app ..."), errors and the app's output are unaffected
+	#[arg(short, long)]
+	pub quiet: bool,
+
+	/// kill the app (and on unix, everything it started) if it runs longer than this many seconds,
+	/// exiting with status 124. ignored for background launches
+	#[arg(long, value_name = "SECS")]
+	pub timeout: Option<u64>,
+
+	/// fail on the first error exit, even if the app sets `retries`
+	#[arg(long)]
+	pub no_retry: bool,
+
+	/// discard the app's stdout/stderr while still waiting for it to exit
+	#[arg(long)]
+	pub null_io: bool,
+
+	/// before launching, print (to stderr) the environment variables ran adds or changes
+	/// compared to its own environment, with the old and new values
+	#[arg(long)]
+	pub print_env: bool,
+
+	/// print the resolved executable, arguments and environment instead of running anything
+	#[arg(short = 'n', long)]
+	pub dry_run: bool,
 }
 
 #[derive(Subcommand)]
 pub enum AppCmd {
 	/// lists all apps (defined in config_path/apps/)
 	#[command(alias = "ls")]
-	List,
+	List {
+		/// only list apps whose [meta] tags include this tag
+		#[arg(short, long)]
+		tag: Option<String>,
+		/// sort the list by name, by path, or by launch count (most launched first)
+		#[arg(short, long, value_enum, default_value_t)]
+		sort: ListSort,
+		/// how to print the list
+		#[arg(short, long, value_enum, default_value_t)]
+		format: ListFormat,
+	},
+
+	/// searches app names (case-insensitive, fuzzy) and lists the best matches
+	#[command(alias = "find")]
+	Search {
+		query: String,
+	},
+
+	/// checks an app definition for problems (missing executables, broken runners, undefined variables)
+	Check {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
+		app: String,
+	},
+
+	/// shows the chain of runner apps (`@name`) a command resolves through, down to the executable
+	Deps {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
+		app: String,
+		/// the command to follow
+		#[arg(short, long, default_value = "launch")]
+		command: String,
+	},
 
 	/// opens an app's definition file in your preferred text editor
 	Edit {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
 		app: String,
+		/// edit even if the file is locked by another edit
+		#[arg(short, long)]
+		force: bool,
+		/// create the app (like `app create`) if it doesn't exist yet
+		#[arg(short, long)]
+		new: bool,
 	},
 
 	/// prints all information about an app
 	#[command(alias = "info")]
 	Print {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
 		app: String,
 		#[arg(short, long)]
 		raw: bool,
+		/// show values with variables expanded
+		#[arg(long)]
+		expand_vars: bool,
+		/// show commands with their runner chains ('@name') resolved
+		#[arg(long)]
+		resolve_runners: bool,
+		/// also show the full environment the 'launch' command would get (inherited, config,
+		/// user env file and app env merged, with variables expanded)
+		#[arg(long, visible_alias = "effective", conflicts_with = "raw")]
+		resolved: bool,
+		/// also show the path of the app's definition file
+		#[arg(long, conflicts_with = "raw")]
+		show_path: bool,
 	},
 
 	/// gets a key's value from an app's definition
 	Get {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
 		app: String,
 		key: Option<String>,
 		#[arg(short, long)]
@@ -102,6 +355,7 @@ pub enum AppCmd {
 
 	/// sets a key's value in an app's definition
 	Set {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
 		app: String,
 		key: String,
 		value: String,
@@ -109,10 +363,35 @@ pub enum AppCmd {
 
 	/// unsets a key in an app's definition
 	Unset {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
 		app: String,
 		key: String,
 	},
 
+	/// renames an app's definition file, updating aliases that point to it
+	Rename {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
+		from: String,
+		to: String,
+	},
+
+	/// moves an app's definition file into a folder under config_path/apps/, keeping its file name
+	#[command(alias = "mv")]
+	Move {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
+		app: String,
+		/// destination folder (e.g. "games/shooters"), or "/" for the top level
+		dest: String,
+	},
+
+	/// duplicates an app's definition file (comments included)
+	#[command(alias = "cp")]
+	Copy {
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
+		src: String,
+		dest: String,
+	},
+
 	/// creates a dummy app definition file
 	#[command(alias = "new")]
 	Create {
@@ -120,30 +399,83 @@ pub enum AppCmd {
 		/// exclude comments and unnecessary data
 		#[arg(short, long)]
 		clean: bool,
+		/// start from a template in config_path/templates/ instead of the built-in one
+		#[arg(short, long, conflicts_with = "clean")]
+		template: Option<String>,
 		/// automatically opens the created file in your text editor
 		#[arg(short, long)]
 		edit: bool,
+		/// skip confirmation prompts (e.g. when a similar app already exists)
+		#[arg(short, long)]
+		yes: bool,
 	},
 
+	/// manages app templates (in config_path/templates/)
+	#[command(subcommand)]
+	Template(TemplateCmd),
+
 	/// deletes an app's definition file (toml only)
 	#[command(alias = "rm")]
 	#[command(alias = "remove")]
 	Delete {
+		/// app to delete, or a glob pattern with --all-matching
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
 		app: String,
+		/// delete every app whose name matches the pattern ('*' within a folder, '**' across folders, '?' one character)
+		#[arg(short, long)]
+		all_matching: bool,
 		/// skip confirmation prompts
 		#[arg(short, long)]
 		yes: bool,
 	},
 }
 
+/// app template management
+#[derive(Subcommand)]
+pub enum TemplateCmd {
+	/// lists all templates
+	#[command(alias = "ls")]
+	List,
+
+	/// saves an existing app definition as a template
+	Save {
+		name: String,
+		/// app to create the template from
+		#[arg(long, add = ArgValueCandidates::new(complete_app_names))]
+		from: String,
+		/// overwrite the template if it already exists
+		#[arg(short, long)]
+		force: bool,
+	},
+}
+
 /// global configuration management
 #[derive(Subcommand)]
 pub enum ConfigCmd {
 	/// prints the current config path
-	Path,
+	Path {
+		/// print the apps directory instead (created if it doesn't exist)
+		#[arg(long)]
+		apps: bool,
+	},
 
 	/// opens the global config file in your preferred text editor
-	Edit,
+	Edit {
+		/// edit even if the file is locked by another edit
+		#[arg(short, long)]
+		force: bool,
+	},
+
+	/// prints the editor used for `edit` commands
+	Editor {
+		/// check that the editor can actually be found on PATH
+		#[arg(short, long)]
+		test: bool,
+	},
+
+	/// checks the config file for unknown keys and values of the wrong type
+	#[command(alias = "check")]
+	Validate,
 
 	/// prints the current config
 	#[command(alias = "info")]
@@ -159,9 +491,12 @@ pub enum ConfigCmd {
 		/// print raw toml when key is not specified
 		#[arg(short, long)]
 		raw: bool,
+		/// print the key's toml subtree straight from the file (e.g. the whole alias table)
+		#[arg(long, requires = "key")]
+		raw_toml: bool,
 	},
 
-	/// sets a key in the config
+	/// sets a key in the config (list and table keys, like `default_args` or `env`, take json or inline toml)
 	Set {
 		key: String,
 		value: String,
@@ -172,6 +507,13 @@ pub enum ConfigCmd {
 		key: String,
 	},
 
+	/// replaces the config file with the default one, backing up the old one to <file>.bak (apps are kept)
+	Reset {
+		/// skip the confirmation prompt
+		#[arg(short, long)]
+		yes: bool,
+	},
+
 	/// generates or regenerates a default config file
 	Init {
 		/// skip confirmation prompts
@@ -191,26 +533,44 @@ pub enum ConfigCmd {
 pub enum AliasCmd {
 	/// lists all app aliases's resolved alias chain
 	#[command(alias = "ls")]
-	List,
+	List {
+		/// show one tree per app, with the aliases that lead to it as branches
+		#[arg(short, long)]
+		tree: bool,
+	},
 
 	/// returns the resolved alias chain for an app alias, or just the imediate value for it
 	Get {
 		key: String,
 		// don't resolve alias chain
-		#[arg(short, long)]
+		#[arg(short, long, conflicts_with = "tree")]
 		unresolved: bool,
+		/// show every alias that ends up at the same app as a tree
+		#[arg(short, long)]
+		tree: bool,
 	},
 
-	/// sets an alias
+	/// sets an alias (warning if it points at nothing)
 	Set {
 		key: String,
+		#[arg(add = ArgValueCandidates::new(complete_app_names))]
 		value: String,
+		/// refuse to set an alias that points at nothing
+		#[arg(long)]
+		strict: bool,
 	},
 
 	/// unsets an alias
 	Unset {
 		key: String,
 	},
+
+	/// renames an alias, keeping its place in the config (aliases pointing at it are updated too)
+	#[command(alias = "mv")]
+	Rename {
+		from: String,
+		to: String,
+	},
 }
 
 /// global variable management
@@ -236,3 +596,81 @@ pub enum VarCmd {
 		key: String,
 	},
 }
+
+// --- implementations ---
+impl From<&LaunchArgs> for LaunchOpts {
+	fn from(args: &LaunchArgs) -> Self {
+		LaunchOpts {
+			background: args.background,
+			foreground: args.foreground,
+			user_env: args.user_env.clone(),
+			env: args.env.clone(),
+			clean_env: args.clean_env,
+			cwd: args.cwd.clone(),
+			quiet: args.quiet,
+			timeout: args.timeout,
+			no_retry: args.no_retry,
+			null_io: args.null_io,
+			print_env: args.print_env,
+			dry_run: args.dry_run,
+		}
+	}
+}
+
+// --- functions ---
+/// (private) app names for shell completion. completion can't see `--config`, so the config
+/// directory and file come from $RANCFG and $RANCFG_FILE (or their defaults)
+fn complete_app_names() -> Vec<CompletionCandidate> {
+	let Some(config_path) = std::env::var_os("RANCFG")
+		.map(PathBuf::from)
+		.or_else(|| default_config_path("ran").ok())
+	else {
+		return Vec::new();
+	};
+	let config_file = std::env::var_os("RANCFG_FILE")
+		.map(PathBuf::from)
+		.unwrap_or_else(|| config_path.join("config.toml"));
+	Launcher::app_names(&config_path, &config_file)
+		.into_iter()
+		.map(CompletionCandidate::new)
+		.collect()
+}
+
+// --- tests ---
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// the app name, args and quiet flag of a parsed `ran launch` command line
+	fn parse_launch(line: &[&str]) -> (String, Vec<String>, bool) {
+		match Cli::try_parse_from(line).unwrap().cmd {
+			Some(Command::Launch { opts, app }) => (app[0].clone(), app[1..].to_vec(), opts.quiet),
+			_ => panic!("not a launch command"),
+		}
+	}
+
+	#[test]
+	fn launch_passes_hyphen_args_through() {
+		let (name, args, quiet) = parse_launch(&["ran", "launch", "game", "--fullscreen", "-w", "--", "-q"]);
+		assert_eq!(name, "game");
+		assert_eq!(args, ["--fullscreen", "-w", "--", "-q"]);
+		assert!(!quiet);
+	}
+
+	#[test]
+	fn launch_options_go_before_the_app_name() {
+		let (name, args, quiet) = parse_launch(&["ran", "launch", "-q", "game"]);
+		assert_eq!(name, "game");
+		assert!(args.is_empty());
+		assert!(quiet);
+
+		// everything after the app name is the app's, even ran's own options and `--`
+		let (name, args, quiet) = parse_launch(&["ran", "launch", "game", "-q"]);
+		assert_eq!(name, "game");
+		assert_eq!(args, ["-q"]);
+		assert!(!quiet);
+		let (_, args, quiet) = parse_launch(&["ran", "launch", "-q", "game", "--", "-b"]);
+		assert_eq!(args, ["--", "-b"]);
+		assert!(quiet);
+	}
+}
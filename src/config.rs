@@ -6,13 +6,18 @@ pub const DEFAULT_CONFIG_CLEAN: &str = include_str!("../res/config_clean.toml");
 use anyhow::{anyhow, bail, Context, Result};
 use colored::Colorize;
 use indexmap::IndexMap as Map;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
 use std::default::Default;
 use std::fmt::{self, Display, Formatter};
 use std::fs;
 use std::path::Path;
-use toml_edit::{table, value, DocumentMut};
-use crate::util::args::parse_bool;
+use std::str::FromStr;
+use toml_edit::{table, value, Array, DocumentMut, Item, TableLike, Value};
+use crate::app::{App, APP_KEYS, CMD_KEYS, META_KEYS};
+use crate::history::DEFAULT_HISTORY_SIZE;
+use crate::util::args::{parse_bool, parse_fragment};
+use crate::util::search::levenshtein;
 use crate::util::table::*;
 
 // --- functions ---
@@ -30,17 +35,147 @@ pub fn new_config_file(config_file: &Path, clean: bool) -> Result<()> {
 	Ok(())
 }
 
+/// (private) reports every key of 'table' that isn't one of 'known', named with 'prefix' (e.g. "apps.doom.")
+fn unknown_keys(text: &str, table: &dyn TableLike, prefix: &str, known: &[&str], problems: &mut Vec<String>) {
+	for (key, _) in table.iter() {
+		if known.contains(&key) {
+			continue;
+		}
+		let line = line_of(text, table, key);
+		let hint = known.iter()
+			.map(|known| (levenshtein(key, known), known))
+			.min()
+			.filter(|(distance, _)| *distance <= 2)
+			.map(|(_, known)| format!(", did you mean '{known}'?"))
+			.unwrap_or_default();
+		problems.push(format!("{line}unknown key '{prefix}{key}'{hint}"));
+	}
+}
+
+/// (private) "line <n>: " for 'key' in 'table', or nothing if its position isn't known
+fn line_of(text: &str, table: &dyn TableLike, key: &str) -> String {
+	table.get_key_value(key)
+		.and_then(|(k, _)| k.span())
+		.map(|span| format!("line {}: ", text[..span.start].matches('\n').count() + 1))
+		.unwrap_or_default()
+}
+
+/// top-level keys config.toml understands (everything else is reported by `ran config validate`)
+pub const CONFIG_KEYS: &[&str] = &[
+	"noninteractive",
+	"drop_empty_args",
+	"terminal_runner",
+	"history_size",
+	"before_launch",
+	"after_launch",
+	"default_args",
+	"alias",
+	"vars",
+	"env",
+	"apps",
+];
+
+/// list-valued settings, addressed by index (e.g. `before_launch.0`, or `default_args.*` for all of them)
+const LIST_KEYS: &[&str] = &["before_launch", "after_launch", "default_args"];
+
 // --- definitions ---
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Config {
 	#[serde(default)]
-	pub noninteractive: bool,
+	pub noninteractive: Interactivity,
+	#[serde(default)]
+	pub drop_empty_args: bool,
+	pub terminal_runner: Option<String>,
+	pub history_size: Option<usize>,
+	/// command lines run before every launch (before the app's own `pre` hooks)
+	pub before_launch: Option<Vec<String>>,
+	/// command lines run after every foreground launch (after the app's own `post` hooks)
+	pub after_launch: Option<Vec<String>>,
+	/// args put before every launch's args (and before the cli args are sandwiched in)
+	pub default_args: Option<Vec<String>>,
 	pub alias: Option<Map<String, String>>,
 	pub vars: Option<Map<String, String>>,
 	pub env: Option<Map<String, String>>,
+	/// apps defined inline as `[apps.<name>]` tables, parsed like app files once they're used
+	pub apps: Option<Map<String, toml::Table>>,
+}
+
+/// whether ran may show interactive prompts, named after the `noninteractive` setting's values:
+/// `"force"`, `"auto"` or `"never"`. the older `true`, `false` and `"always"` are read as force, auto and never.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Interactivity {
+	/// noninteractive only if stdout isn't a terminal, checked on every run
+	#[default]
+	Auto,
+	/// never noninteractive: prompt whenever there's a terminal to prompt on (prompts are drawn on stderr),
+	/// even if stdout is piped
+	Never,
+	/// always noninteractive: never prompt, fail on anything that would need one
+	Force,
 }
 
 // --- implementations ---
+impl Interactivity {
+	/// whether prompts may be shown right now
+	pub fn allowed(self) -> bool {
+		match self {
+			Interactivity::Auto => atty::is(atty::Stream::Stdout),
+			Interactivity::Never => atty::is(atty::Stream::Stderr),
+			Interactivity::Force => false,
+		}
+	}
+}
+
+impl Display for Interactivity {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Interactivity::Auto => "auto",
+			Interactivity::Never => "never",
+			Interactivity::Force => "force",
+		})
+	}
+}
+
+impl FromStr for Interactivity {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		match s.to_lowercase().trim() {
+			"auto" => Ok(Interactivity::Auto),
+			"never" | "always" => Ok(Interactivity::Never),
+			"force" => Ok(Interactivity::Force),
+			_ => match parse_bool(s) {
+				Some(true) => Ok(Interactivity::Force),
+				Some(false) => Ok(Interactivity::Auto),
+				None => bail!("parse error: '{s}' is not 'force', 'auto' or 'never'"),
+			},
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Interactivity {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+		#[derive(Deserialize)]
+		#[serde(untagged)]
+		enum Raw {
+			Bool(bool),
+			Name(String),
+		}
+
+		match Raw::deserialize(deserializer)? {
+			Raw::Bool(true) => Ok(Interactivity::Force),
+			Raw::Bool(false) => Ok(Interactivity::Auto),
+			Raw::Name(name) => name.parse().map_err(serde::de::Error::custom),
+		}
+	}
+}
+
+impl Serialize for Interactivity {
+	fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_string())
+	}
+}
+
 impl Config {
 	/// loads config from toml (fails if config file doesn't exist)
 	pub fn load(config_file: &Path) -> Result<Self> {
@@ -48,10 +183,68 @@ impl Config {
 			bail!("config file does not exist in '{}'", config_file.display());
 		}
 
-		let config_str = std::fs::read_to_string(config_file)?;
-		let config: Config = toml::from_str(&config_str)?;
+		let config_str = std::fs::read_to_string(config_file)
+			.with_context(|| format!("failed to read config at '{}'", config_file.display()))?;
+		toml::from_str(&config_str)
+			.map_err(|e| anyhow!("invalid config '{}': {e}", config_file.display()))
+	}
+
+	/// checks a config file for syntax errors, unknown keys and values of the wrong type,
+	/// returning a description of every problem found (with line numbers where possible)
+	pub fn validate(config_file: &Path) -> Result<Vec<String>> {
+		let text = fs::read_to_string(config_file)
+			.with_context(|| format!("failed to read config at '{}'", config_file.display()))?;
+		let doc = match toml_edit::Document::parse(text.as_str()) {
+			Ok(doc) => doc,
+			Err(e) => return Ok(vec![format!("syntax error: {e}")]),
+		};
+		let mut problems = Vec::new();
+		let root = doc.as_table();
+
+		// 1. unknown keys (serde would silently ignore these), also in inline apps
+		unknown_keys(&text, root, "", CONFIG_KEYS, &mut problems);
+		let apps = root.get("apps").and_then(Item::as_table_like);
+		for (name, app) in apps.iter().flat_map(|apps| apps.iter()) {
+			let Some(app) = app.as_table_like() else { continue };
+			unknown_keys(&text, app, &format!("apps.{name}."), APP_KEYS, &mut problems);
+			if let Some(meta) = app.get("meta").and_then(Item::as_table_like) {
+				unknown_keys(&text, meta, &format!("apps.{name}.meta."), META_KEYS, &mut problems);
+			}
+			let cmds = app.get("cmds").and_then(Item::as_table_like);
+			for (cmd_name, cmd) in cmds.iter().flat_map(|cmds| cmds.iter()) {
+				if let Some(cmd) = cmd.as_table_like() {
+					unknown_keys(&text, cmd, &format!("apps.{name}.cmds.{cmd_name}."), CMD_KEYS, &mut problems);
+				}
+			}
+		}
+
+		// 2. every entry of the tables of strings (e.g. an `[alias.games]` sub-table)
+		let mut not_strings = false;
+		for key in ["alias", "vars", "env"] {
+			let Some(entries) = root.get(key).and_then(Item::as_table_like) else { continue };
+			for (name, item) in entries.iter() {
+				if !item.is_str() {
+					let line = line_of(&text, entries, name);
+					problems.push(format!("{line}'{key}.{name}' must be a string, not {}", item.type_name()));
+					not_strings = true;
+				}
+			}
+		}
+
+		// 3. wrong types (toml's error already points at the offending line)
+		match toml::from_str::<Config>(&text) {
+			// toml stops at the first wrong type, which was already reported above
+			Err(_) if not_strings => {}
+			Err(e) => problems.push(e.to_string().trim_end().to_string()),
+			// 4. inline apps that wouldn't load
+			Ok(config) => for (name, table) in config.apps.unwrap_or_default() {
+				if let Err(e) = toml::Value::Table(table).try_into::<App>() {
+					problems.push(format!("inline app '{name}': {}", e.message()));
+				}
+			},
+		}
 
-		Ok(config)
+		Ok(problems)
 	}
 
 	/// saves config file with proper formatting
@@ -71,7 +264,38 @@ impl Config {
 		};
 
 		// 1. general config
-		doc["noninteractive"] = value(self.noninteractive);
+		// the stored form is kept while it still means the same (e.g. an older `noninteractive = true`)
+		let stored = doc.get("noninteractive")
+			.and_then(|item| item.as_bool().map(|b| b.to_string()).or(item.as_str().map(String::from)))
+			.and_then(|stored| stored.parse::<Interactivity>().ok());
+		if stored != Some(self.noninteractive) {
+			doc["noninteractive"] = value(self.noninteractive.to_string());
+		}
+		// like the optional keys, only written once it's been changed from the default
+		if self.drop_empty_args || doc.contains_key("drop_empty_args") {
+			doc["drop_empty_args"] = value(self.drop_empty_args);
+		}
+		if let Some(runner) = &self.terminal_runner {
+			doc["terminal_runner"] = value(runner.clone());
+		} else {
+			doc.as_table_mut().remove("terminal_runner");
+		}
+		if let Some(size) = self.history_size {
+			doc["history_size"] = value(size as i64);
+		} else {
+			doc.as_table_mut().remove("history_size");
+		}
+		for key in LIST_KEYS {
+			if let Some(items) = self.list(key) {
+				let mut arr = Array::new();
+				for item in items {
+					arr.push(item.clone());
+				}
+				doc[key] = Item::Value(Value::Array(arr));
+			} else {
+				doc.as_table_mut().remove(key);
+			}
+		}
 
 		// 2. alias
 		if let Some(alias) = &self.alias {
@@ -109,6 +333,8 @@ impl Config {
 			doc.as_table_mut().remove("env");
 		}
 
+		// (inline apps are left as they are in the file)
+
 		// 5. write back
 		fs::write(config_file, doc.to_string())
 			.with_context(|| format!("failed to write app to {:?}", config_file.display()))?;
@@ -116,6 +342,31 @@ impl Config {
 		Ok(())
 	}
 
+	/// reads the toml subtree at a dotted key straight from the config file, keeping its
+	/// comments and formatting (tables are printed as their contents, values as toml literals)
+	pub fn get_raw_toml(config_file: &Path, query: &str) -> Result<String> {
+		let text = fs::read_to_string(config_file)
+			.with_context(|| format!("failed to read config at '{}'", config_file.display()))?;
+		let doc = text.parse::<DocumentMut>().context("failed to parse config toml")?;
+
+		let mut item = doc.as_item();
+		for part in query.split('.') {
+			item = item.get(part).ok_or_else(|| anyhow!("invalid key '{query}'"))?;
+		}
+
+		Ok(match item {
+			Item::Table(table) => {
+				let mut out = DocumentMut::new();
+				*out.as_table_mut() = table.clone();
+				out.as_table_mut().decor_mut().clear();
+				out.to_string().trim().to_string()
+			}
+			Item::Value(value) => value.to_string().trim().to_string(),
+			Item::ArrayOfTables(tables) => tables.to_string().trim().to_string(),
+			Item::None => bail!("invalid key '{query}'"),
+		})
+	}
+
 	// getters and setters
 
 	pub fn get(&self, query: &str) -> Option<String> {
@@ -123,6 +374,38 @@ impl Config {
 		self.get_slice(parts.as_slice())
 	}
 
+	/// like `get`, but as json: booleans and numbers keep their type, and '*' gives the whole config
+	pub fn get_json(&self, query: &str) -> Option<serde_json::Value> {
+		let parts: Vec<&str> = query.split('.').collect();
+		match parts.as_slice() {
+			["*"] => serde_json::to_value(self).ok(),
+			["drop_empty_args"] => Some(json!(self.drop_empty_args)),
+			["history_size"] => Some(json!(self.history_size.unwrap_or(DEFAULT_HISTORY_SIZE))),
+			[key] | [key, "*"] if LIST_KEYS.contains(key) => Some(json!(self.list(key)?)),
+			parts => self.get_slice(parts).map(serde_json::Value::String),
+		}
+	}
+
+	/// (private) the list-valued setting 'key' (one of LIST_KEYS)
+	fn list(&self, key: &str) -> Option<&Vec<String>> {
+		match key {
+			"before_launch" => self.before_launch.as_ref(),
+			"after_launch" => self.after_launch.as_ref(),
+			"default_args" => self.default_args.as_ref(),
+			_ => None,
+		}
+	}
+
+	/// (private) mutable version of `list`
+	fn list_mut(&mut self, key: &str) -> Result<&mut Option<Vec<String>>> {
+		match key {
+			"before_launch" => Ok(&mut self.before_launch),
+			"after_launch" => Ok(&mut self.after_launch),
+			"default_args" => Ok(&mut self.default_args),
+			_ => bail!("'{key}' is not a list"),
+		}
+	}
+
 	pub fn set(&mut self, query: &str, value: String) -> Result<()> {
 		let parts: Vec<&str> = query.split('.').collect();
 		self.set_slice(parts.as_slice(), value)
@@ -137,6 +420,18 @@ impl Config {
 		match parts {
 			["*"] => Some(format!("{}", self)),
 			["noninteractive"] => Some(self.noninteractive.to_string()),
+			["drop_empty_args"] => Some(self.drop_empty_args.to_string()),
+			["terminal_runner"] => self.terminal_runner.clone(),
+			["history_size"] => Some(self.history_size.unwrap_or(DEFAULT_HISTORY_SIZE).to_string()),
+			// a bare list key is the whole list, like `<key>.*`
+			[key] if LIST_KEYS.contains(key) => Some(self.list(key)?.join("\n")),
+			[key, num] if LIST_KEYS.contains(key) => {
+				let items = self.list(key)?;
+				match *num {
+					"*" => Some(items.join("\n")),
+					_ => items.get(num.parse::<usize>().ok()?).cloned(),
+				}
+			}
 			["alias", k] => self.alias.as_ref()?.get(*k).cloned(),
 			["vars", k] => self.vars.as_ref()?.get(*k).cloned(),
 			["env", k] => self.env.as_ref()?.get(*k).cloned(),
@@ -146,8 +441,36 @@ impl Config {
 
 	pub fn set_slice(&mut self, parts: &[&str], value: String) -> Result<()> {
 		match parts {
-			["noninteractive"] => self.noninteractive = parse_bool(&value)
+			["noninteractive"] => self.noninteractive = value.parse()?,
+			["drop_empty_args"] => self.drop_empty_args = parse_bool(&value)
 				.ok_or(anyhow!("parse error: '{value}' is not a boolean"))?,
+			["terminal_runner"] => self.terminal_runner = Some(value),
+			["history_size"] => self.history_size = Some(value.trim().parse()
+				.map_err(|_| anyhow!("parse error: '{value}' is not a positive integer"))?),
+			// whole lists and tables, e.g. '["--a", "--b"]' or '{ WINEDEBUG = "-all" }'
+			[key] if LIST_KEYS.contains(key) => *self.list_mut(key)? = Some(
+				parse_fragment(&value).map_err(|e| anyhow!("'{key}' expects an array of strings, e.g. '[\"--a\", \"--b\"]': {e}"))?
+			),
+			[key @ ("alias" | "vars" | "env")] => {
+				let map = parse_fragment(&value)
+					.map_err(|e| anyhow!("'{key}' expects a table of strings, e.g. '{{ KEY = \"value\" }}': {e}"))?;
+				match *key {
+					"alias" => self.alias = Some(map),
+					"vars" => self.vars = Some(map),
+					_ => self.env = Some(map),
+				}
+			}
+			[key, num] if LIST_KEYS.contains(key) => {
+				let items = self.list_mut(key)?.get_or_insert_default();
+				let index = num.parse::<usize>()
+					.map_err(|_| anyhow!("invalid {key} index '{}'", num))?;
+				// setting the index right after the last item adds a new one
+				match index.cmp(&items.len()) {
+					std::cmp::Ordering::Less => items[index] = value,
+					std::cmp::Ordering::Equal => items.push(value),
+					std::cmp::Ordering::Greater => bail!("{key} index {} out of bounds", index),
+				}
+			}
 
 			["alias", k] => {
 				let alias = self.alias.get_or_insert_default();
@@ -174,7 +497,27 @@ impl Config {
 			["*"] => {
 				*self = Default::default();
 			}
-			["noninteractive"] => self.noninteractive = false,
+			["noninteractive"] => self.noninteractive = Interactivity::Auto,
+			["drop_empty_args"] => self.drop_empty_args = false,
+			["terminal_runner"] => self.terminal_runner = None,
+			["history_size"] => self.history_size = None,
+			[key, num] if LIST_KEYS.contains(key) => {
+				let items = self.list_mut(key)?;
+				match *num {
+					"*" => *items = None,
+					_ => {
+						let index = num.parse::<usize>()
+							.map_err(|_| anyhow!("invalid {key} index '{}'", num))?;
+						let items = items
+							.as_mut()
+							.ok_or_else(|| anyhow!("{key} not initialized"))?;
+						if index >= items.len() {
+							bail!("{key} index {} out of bounds", index);
+						}
+						items.remove(index);
+					}
+				}
+			}
 
 			["alias", k] => match *k {
 				"*" => {
@@ -225,6 +568,23 @@ impl Display for Config {
 		// 1. general settings
 		let mut general = Map::new();
 		general.insert("Noninteractive".bright_cyan().to_string(), self.noninteractive.to_string());
+		general.insert("Drop Empty Args".bright_cyan().to_string(), self.drop_empty_args.to_string());
+		if let Some(runner) = &self.terminal_runner {
+			general.insert("Terminal Runner".bright_cyan().to_string(), runner.clone());
+		}
+		general.insert(
+			"History Size".bright_cyan().to_string(),
+			self.history_size.unwrap_or(DEFAULT_HISTORY_SIZE).to_string()
+		);
+		if let Some(hooks) = self.before_launch.as_ref().filter(|h| !h.is_empty()) {
+			general.insert("Before Launch".bright_cyan().to_string(), hooks.join("\n"));
+		}
+		if let Some(hooks) = self.after_launch.as_ref().filter(|h| !h.is_empty()) {
+			general.insert("After Launch".bright_cyan().to_string(), hooks.join("\n"));
+		}
+		if let Some(args) = self.default_args.as_ref().filter(|a| !a.is_empty()) {
+			general.insert("Default Args".bright_cyan().to_string(), shell_words::join(args));
+		}
 		sections.insert(format!("{}", "General Settings".bright_cyan().bold()), general);
 
 		// 2. app aliases
@@ -279,3 +639,119 @@ impl Display for Config {
 		Ok(())
 	}
 }
+
+// --- tests ---
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	#[test]
+	fn set_creates_and_overwrites_table_keys() {
+		let dir = TempDir::new().unwrap();
+		let file = dir.path().join("config.toml");
+		let mut config = Config::default();
+
+		for table in ["alias", "vars", "env"] {
+			let key = format!("{table}.new");
+			config.set(&key, "first".into()).unwrap();
+			assert_eq!(config.get(&key).as_deref(), Some("first"), "creating {key}");
+			config.set(&key, "second".into()).unwrap();
+			assert_eq!(config.get(&key).as_deref(), Some("second"), "overwriting {key}");
+		}
+
+		// and the keys survive a save
+		config.save(&file).unwrap();
+		let loaded = Config::load(&file).unwrap();
+		for table in ["alias", "vars", "env"] {
+			assert_eq!(loaded.get(&format!("{table}.new")).as_deref(), Some("second"));
+		}
+	}
+
+	#[test]
+	fn reads_old_and_new_interactivity_values() {
+		#[derive(Deserialize)]
+		struct General {
+			noninteractive: Interactivity,
+		}
+		let read = |value: &str| toml::from_str::<General>(&format!("noninteractive = {value}")).unwrap().noninteractive;
+
+		assert_eq!(read("\"force\""), Interactivity::Force);
+		assert_eq!(read("\"auto\""), Interactivity::Auto);
+		assert_eq!(read("\"never\""), Interactivity::Never);
+		assert_eq!(read("true"), Interactivity::Force);
+		assert_eq!(read("false"), Interactivity::Auto);
+		assert_eq!(read("\"always\""), Interactivity::Never);
+
+		// `config set` takes the same values and writes them back by name
+		for value in ["force", "auto", "never"] {
+			let mut config = Config::default();
+			config.set("noninteractive", value.into()).unwrap();
+			assert_eq!(config.get("noninteractive").as_deref(), Some(value));
+		}
+		assert!("sometimes".parse::<Interactivity>().is_err());
+	}
+
+	#[test]
+	fn validate_looks_into_tables_and_inline_apps() {
+		let dir = TempDir::new().unwrap();
+		let file = dir.path().join("config.toml");
+		fs::write(&file, [
+			"[alias.games]",
+			"doom = \"games/doom\"",
+			"",
+			"[apps.htop]",
+			"backgrund = true",
+			"",
+			"[apps.htop.cmds.launch]",
+			"bin = \"htop\"",
+			"detach = 5",
+		].join("\n")).unwrap();
+
+		assert_eq!(Config::validate(&file).unwrap(), [
+			"line 5: unknown key 'apps.htop.backgrund', did you mean 'background'?",
+			"line 9: unknown key 'apps.htop.cmds.launch.detach'",
+			"line 1: 'alias.games' must be a string, not table",
+		]);
+	}
+
+	#[test]
+	fn a_bare_list_key_gets_the_whole_list() {
+		let mut config = Config::default();
+		config.set("default_args", "[\"--a\", \"--b\"]".into()).unwrap();
+
+		assert_eq!(config.get("default_args").as_deref(), Some("--a\n--b"));
+		assert_eq!(config.get("default_args"), config.get("default_args.*"));
+		assert_eq!(config.get_json("default_args"), Some(json!(["--a", "--b"])));
+	}
+
+	#[test]
+	fn save_keeps_the_stored_interactivity_form() {
+		let dir = TempDir::new().unwrap();
+		let file = dir.path().join("config.toml");
+		fs::write(&file, "noninteractive = true\n").unwrap();
+
+		let mut config = Config::load(&file).unwrap();
+		config.set("terminal_runner", "kitty".into()).unwrap();
+		config.save(&file).unwrap();
+		assert!(fs::read_to_string(&file).unwrap().contains("noninteractive = true\n"));
+
+		// a new value is written by name
+		config.set("noninteractive", "never".into()).unwrap();
+		config.save(&file).unwrap();
+		assert!(fs::read_to_string(&file).unwrap().contains("noninteractive = \"never\"\n"));
+	}
+
+	#[test]
+	fn save_leaves_out_a_default_drop_empty_args() {
+		let dir = TempDir::new().unwrap();
+		let file = dir.path().join("config.toml");
+
+		Config::default().save(&file).unwrap();
+		assert!(!fs::read_to_string(&file).unwrap().contains("drop_empty_args"));
+
+		let config = Config { drop_empty_args: true, ..Config::default() };
+		config.save(&file).unwrap();
+		assert!(fs::read_to_string(&file).unwrap().contains("drop_empty_args = true"));
+	}
+}
@@ -0,0 +1,250 @@
+// --- imports ---
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use ran_launcher::app::App;
+use ran_launcher::config::{new_config_file, Config};
+use ran_launcher::launcher::{ambiguous_names, Launcher};
+use ran_launcher::util::fs::{fallback_editor, find_in_path, preferred_editor};
+
+// --- definitions ---
+#[derive(Clone, Copy, PartialEq)]
+pub enum Status {
+	Pass,
+	/// not a problem, just something worth knowing (e.g. whether colors are on)
+	Info,
+	Warn,
+	Fail,
+}
+
+/// a repair `ran doctor --fix` can make
+pub enum Fix {
+	/// creates config_path/apps
+	CreateAppsDir,
+	/// writes a default config file, backing up an existing (broken) one to <file>.bak first
+	RecreateConfig,
+	/// removes the given aliases from the config
+	RemoveAliases(Vec<String>),
+}
+
+/// the result of a single check
+pub struct Finding {
+	pub status: Status,
+	pub message: String,
+	pub fix: Option<Fix>,
+}
+
+// --- implementations ---
+impl Display for Status {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Status::Pass => write!(f, "{}", "[ ok ]".green().bold()),
+			Status::Info => write!(f, "{}", "[info]".bright_blue().bold()),
+			Status::Warn => write!(f, "{}", "[warn]".yellow().bold()),
+			Status::Fail => write!(f, "{}", "[fail]".red().bold()),
+		}
+	}
+}
+
+impl Finding {
+	fn new(status: Status, message: impl Into<String>) -> Self {
+		Self { status, message: message.into(), fix: None }
+	}
+
+	fn with_fix(mut self, fix: Fix) -> Self {
+		self.fix = Some(fix);
+		self
+	}
+}
+
+impl Fix {
+	/// what applying this fix will do, phrased as a question-friendly action
+	pub fn describe(&self) -> String {
+		match self {
+			Fix::CreateAppsDir => "create the apps directory".into(),
+			Fix::RecreateConfig => "recreate config.toml from defaults (backing up the old one)".into(),
+			Fix::RemoveAliases(names) => format!("remove alias(es) '{}'", names.join("', '")),
+		}
+	}
+
+	pub fn apply(&self, config_path: &Path, config_file: &Path) -> Result<()> {
+		match self {
+			Fix::CreateAppsDir => fs::create_dir_all(config_path.join("apps"))?,
+			Fix::RecreateConfig => {
+				if config_file.exists() {
+					let mut backup = config_file.as_os_str().to_owned();
+					backup.push(".bak");
+					let backup = PathBuf::from(backup);
+					fs::rename(config_file, &backup)
+						.map_err(|e| anyhow!("failed to back up config: {e}"))?;
+					eprintln!("backed up the old config to '{}'", backup.display());
+				}
+				new_config_file(config_file, false)?;
+			}
+			Fix::RemoveAliases(names) => {
+				let mut config = Config::load(config_file)?;
+				if let Some(alias) = config.alias.as_mut() {
+					for name in names {
+						alias.shift_remove(name);
+					}
+				}
+				config.save(config_file)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+// --- functions ---
+/// checks the setup in 'config_path' (with its config at 'config_file') for common problems
+pub fn diagnose(config_path: &Path, config_file: &Path) -> Vec<Finding> {
+	let mut findings = Vec::new();
+
+	// 0. config directory
+	if !config_path.is_dir() {
+		findings.push(
+			Finding::new(Status::Fail, format!("config directory '{}' does not exist", config_path.display()))
+				.with_fix(Fix::CreateAppsDir)
+		);
+	} else {
+		// the only reliable test is actually writing something
+		let probe = config_path.join(".ran-doctor");
+		match fs::write(&probe, "") {
+			Ok(()) => {
+				let _ = fs::remove_file(&probe);
+				findings.push(Finding::new(Status::Pass, "config directory is writable"));
+			}
+			Err(e) => findings.push(Finding::new(
+				Status::Fail,
+				format!("config directory '{}' is not writable: {e}", config_path.display())
+			)),
+		}
+	}
+
+	// 1. config file
+	let config = if !config_file.exists() {
+		findings.push(
+			Finding::new(Status::Fail, format!("config file '{}' does not exist", config_file.display()))
+				.with_fix(Fix::RecreateConfig)
+		);
+		None
+	} else {
+		match Config::load(config_file) {
+			Ok(config) => {
+				findings.push(Finding::new(Status::Pass, "config file parses"));
+				Some(config)
+			}
+			Err(e) => {
+				findings.push(
+					Finding::new(Status::Fail, format!("config file can't be loaded: {e}"))
+						.with_fix(Fix::RecreateConfig)
+				);
+				None
+			}
+		}
+	};
+
+	// 2. apps directory
+	if config_path.join("apps").is_dir() {
+		findings.push(Finding::new(Status::Pass, "apps directory exists"));
+	} else {
+		findings.push(
+			Finding::new(Status::Fail, "apps directory does not exist")
+				.with_fix(Fix::CreateAppsDir)
+		);
+	}
+
+	// 3. aliases pointing at nothing
+	if let Some(alias) = config.as_ref().and_then(|c| c.alias.as_ref()) {
+		let apps = App::find_all(config_path);
+		let dangling: Vec<String> = alias.iter()
+			.filter(|(_, target)| {
+				let target = target.trim().trim_matches('/');
+				!alias.contains_key(target) && !apps.keys().any(|name| {
+					name == target || name.rsplit('/').next() == Some(target)
+				})
+			})
+			.map(|(name, _)| name.clone())
+			.collect();
+		if dangling.is_empty() {
+			findings.push(Finding::new(Status::Pass, "all aliases point at existing apps"));
+		} else {
+			findings.push(
+				Finding::new(Status::Warn, format!("alias(es) pointing at nothing: '{}'", dangling.join("', '")))
+					.with_fix(Fix::RemoveAliases(dangling))
+			);
+		}
+	}
+
+	// 4. app definitions (files and inline ones)
+	let files = App::find_all(config_path);
+	let inline = config.as_ref().and_then(|c| c.apps.as_ref());
+	let failed_files = files.values()
+		.filter(|path| fs::read_to_string(path).ok().and_then(|text| toml::from_str::<App>(&text).ok()).is_none())
+		.count();
+	let failed_inline = inline.iter()
+		.flat_map(|apps| apps.values())
+		.filter(|table| toml::Value::Table((*table).clone()).try_into::<App>().is_err())
+		.count();
+	let failed = failed_files + failed_inline;
+	let total = files.len() + inline.map_or(0, |apps| apps.len());
+	if failed == 0 {
+		findings.push(Finding::new(Status::Pass, format!("all {total} app definition(s) parse")));
+	} else {
+		findings.push(Finding::new(
+			Status::Fail,
+			format!("{failed} of {total} app definition(s) don't parse (see 'ran validate-all')")
+		));
+	}
+
+	// 5. apps sharing a name
+	let ambiguous = ambiguous_names(&Launcher::app_names(config_path, config_file));
+	if ambiguous.is_empty() {
+		findings.push(Finding::new(Status::Pass, "no two apps share a name"));
+	}
+	for warning in ambiguous {
+		findings.push(Finding::new(Status::Warn, warning));
+	}
+
+	// 6. editor
+	match preferred_editor() {
+		Some(editor) => {
+			let program = shell_words::split(&editor).ok().and_then(|words| words.into_iter().next());
+			if program.as_deref().and_then(find_in_path).is_some() {
+				findings.push(Finding::new(Status::Pass, format!("editor '{editor}' is on PATH")));
+			} else {
+				findings.push(Finding::new(Status::Warn, format!("editor '{editor}' ($VISUAL/$EDITOR) is not on PATH")));
+			}
+		}
+		None => findings.push(Finding::new(
+			Status::Warn,
+			format!("neither $VISUAL nor $EDITOR is set, edits open in the system default app or '{}'", fallback_editor())
+		)),
+	}
+
+	// 7. terminal runner
+	if let Some(runner) = config.as_ref().and_then(|c| c.terminal_runner.as_ref()) {
+		match shell_words::split(runner).ok().and_then(|words| words.into_iter().next()) {
+			Some(program) if find_in_path(&program).is_some() => {
+				findings.push(Finding::new(Status::Pass, format!("terminal_runner '{program}' is on PATH")));
+			}
+			_ => findings.push(Finding::new(
+				Status::Fail,
+				format!("terminal_runner '{runner}' is not on PATH, background launches will fail")
+			)),
+		}
+	}
+
+	// 8. terminal and colors
+	let tty = atty::is(atty::Stream::Stdout);
+	let colors = colored::control::SHOULD_COLORIZE.should_colorize();
+	findings.push(Finding::new(Status::Info, format!(
+		"output is {}a terminal, colors are {}",
+		if tty { "" } else { "not " },
+		if colors { "on" } else { "off (--no-color, $NO_COLOR or no terminal)" }
+	)));
+
+	findings
+}
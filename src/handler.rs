@@ -1,30 +1,43 @@
 // --- imports ---
 use anyhow::{anyhow, bail, Result};
+use clap::CommandFactory;
+use clap_complete::env::Shells;
 use colored::Colorize;
+use indexmap::IndexMap as Map;
+use serde_json::json;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
-use terminal_size::{terminal_size, Width};
-use crate::app::{new_app, sanitize_app_name};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use ran_launcher::app::{new_app, sanitize_app_name, save_template, App, AppInfo};
+use ran_launcher::config::{new_config_file, Config};
+use ran_launcher::history::{HistoryEntry, LaunchCounter, CSV_HEADER};
+use ran_launcher::launcher::{ambiguous_names, mask_secrets, AppSource, Launcher, ResolveOpts};
+use ran_launcher::resolver::Resolver;
+use ran_launcher::util::search::glob_match;
+use ran_launcher::util::fs::{fallback_editor, find_in_path, open_in_editor_locked, preferred_editor};
+use crate::bundle::Bundle;
 use crate::cli::*;
-use crate::config::{new_config_file, Config};
-use crate::launcher::Launcher;
-use crate::resolver::Resolver;
-use crate::util::fs::open_in_editor;
+use crate::doctor::{self, Status};
+use crate::stats::Stats;
 
 // --- definitions ---
 pub struct CommandHandler {
 	pub config_path: PathBuf,
+	/// config.toml, in config_path unless `--config-file` says otherwise
+	pub config_file: PathBuf,
+	pub json: bool,
 }
 
 // --- implementations ---
 impl CommandHandler {
-	pub fn new(config_path: PathBuf) -> Self {
-		Self { config_path }
+	pub fn new(config_path: PathBuf, config_file: Option<PathBuf>, json: bool) -> Self {
+		let config_file = config_file.unwrap_or_else(|| config_path.join("config.toml"));
+		Self { config_path, config_file, json }
 	}
 
 	pub fn init_config(&self) -> Result<Config> {
-		let config_file = self.config_path.join("config.toml");
+		let config_file = self.config_file.clone();
 		new_config_file(&config_file, false)?;
 		Config::load(&config_file)
 	}
@@ -34,19 +47,156 @@ impl CommandHandler {
 		Launcher::init(&self.config_path, config)
 	}
 
+	/// like init_launcher, but skips scanning for apps (see Launcher::init_config_only)
+	pub fn init_config_launcher(&self) -> Result<Launcher> {
+		Ok(Launcher::init_config_only(&self.config_path, self.init_config()?))
+	}
+
 	pub fn handle_command(&self, cmd: Command) -> Result<()> {
 		match cmd {
-			Command::Launch { name, args, background } => {
-				self.handle_launch("launch", &name, args, background)?
+			Command::Launch { opts, app } => {
+				let (name, args) = split_app_args(app);
+				self.handle_launch("launch", &name, args, &opts)?
 			}
-			Command::Cmd { cmd, name, args, background } => {
-				self.handle_launch(&cmd, &name, args, background)?
+			Command::Cmd { opts, cmd, app } => {
+				let (name, args) = split_app_args(app);
+				self.handle_launch(&cmd, &name, args, &opts)?
+			}
+
+			Command::Group { names, atomic, opts } => {
+				if opts.args_from_stdin {
+					bail!("--args-from-stdin can't be used when launching a group");
+				}
+				let l = self.init_launcher()?;
+				l.launch_group(&names, env::vars().collect(), atomic, &(&opts).into())?
 			}
 
 			Command::App(app_cmd) => self.handle_app_cmd(app_cmd)?,
 			Command::Config(config_cmd) => self.handle_config_cmd(config_cmd)?,
 			Command::Alias(alias_cmd) => self.handle_alias_cmd(alias_cmd)?,
 			Command::Var(var_cmd) => self.handle_var_cmd(var_cmd)?,
+
+			Command::History { limit, csv } => {
+				let history = HistoryEntry::load_all(&self.config_path)?;
+				let skip = limit.map_or(0, |n| history.len().saturating_sub(n));
+				let history = &history[skip..];
+				if self.json {
+					println!("{}", serde_json::to_string_pretty(history)?);
+					return Ok(());
+				}
+				if csv {
+					println!("{CSV_HEADER}");
+					for entry in history {
+						println!("{}", entry.to_csv());
+					}
+					return Ok(());
+				}
+				if history.is_empty() {
+					eprintln!("no launches recorded yet");
+				}
+				for entry in history {
+					println!(
+						"{} {} {} {} {}",
+						entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string().bright_black(),
+						entry.app.yellow(),
+						format!("({})", entry.cmd).bright_black(),
+						"--".bright_black(),
+						shell_words::join(std::iter::once(&entry.bin).chain(&entry.args)).white()
+					);
+				}
+			}
+			Command::Stats => {
+				let stats = Stats::collect(&self.init_launcher()?)?;
+				if self.json {
+					println!("{}", serde_json::to_string_pretty(&stats)?);
+				} else {
+					println!("{stats}");
+				}
+			}
+			Command::Which { app } => {
+				let l = self.init_launcher()?;
+				println!("{}", self.app_location(l.find_app(&app)?, true));
+			}
+			Command::ValidateAll => {
+				let l = self.init_launcher()?;
+				// the parse errors name the file, with the line and column of the problem
+				let results: Vec<(&String, &AppSource, Option<String>)> = l.apps.iter()
+					.map(|(name, source)| (name, source, l.load_app_from(source).err().map(|e| e.to_string())))
+					.collect();
+				let failed = results.iter().filter(|(_, _, error)| error.is_some()).count();
+
+				if self.json {
+					let list: Vec<_> = results.iter()
+						.map(|(name, source, error)| json!({ "name": name, "path": self.app_location(source, false), "error": error }))
+						.collect();
+					println!("{}", serde_json::to_string_pretty(&list)?);
+				} else {
+					for (name, _, error) in &results {
+						match error {
+							None => println!("{} {name}", Status::Pass),
+							Some(error) => {
+								println!("{} {name}", Status::Fail);
+								for line in error.trim_end().lines() {
+									println!("    {line}");
+								}
+							}
+						}
+					}
+					for warning in ambiguous_names(l.apps.keys()) {
+						println!("{} {warning}", Status::Warn);
+					}
+				}
+				if failed > 0 {
+					bail!("{failed} of {} app definition(s) failed to parse", results.len());
+				}
+				if !self.json {
+					eprintln!("all {} app definition(s) parse", results.len());
+				}
+			}
+			Command::Export { out } => {
+				let text = Bundle::export(&self.config_path, &self.config_file)?.to_toml()?;
+				match out {
+					Some(out) => {
+						fs::write(&out, text)?;
+						eprintln!("exported your setup to '{}'", out.display());
+					}
+					None => print!("{text}"),
+				}
+			}
+			Command::Import {
+				file,
+				force,
+				#[cfg(feature = "verify")]
+				verify,
+			} => {
+				let text = fs::read_to_string(&file)
+					.map_err(|e| anyhow!("failed to read '{}': {e}", file.display()))?;
+				#[cfg(feature = "verify")]
+				if let Some(key_file) = verify {
+					let mut signature_file = file.clone().into_os_string();
+					signature_file.push(".minisig");
+					crate::bundle::verify_signature(text.as_bytes(), &key_file, Path::new(&signature_file))?;
+					eprintln!("'{}' is signed by '{}'", file.display(), key_file.display());
+				}
+				let written = Bundle::parse(&text)?.import(&self.config_path, &self.config_file, force)?;
+				eprintln!("imported {} file(s) into '{}'", written.len(), self.config_path.display());
+			}
+			Command::Doctor { fix, yes } => self.doctor(fix, yes)?,
+			Command::Completions { shell } => {
+				// the script calls `COMPLETE=<shell> ran` (see main), which completes using the current apps
+				let cli = Cli::command();
+				let name = cli.get_name();
+				let shells = Shells::builtins();
+				let completer = shells.completer(&shell.to_string())
+					.ok_or_else(|| anyhow!("completions for {shell} are not supported"))?;
+				completer.write_registration(COMPLETE_VAR, name, name, name, &mut io::stdout())?;
+			}
+			Command::CompleteApps => {
+				// never creates config files, unlike init_launcher()
+				for name in Launcher::app_names(&self.config_path, &self.config_file) {
+					println!("{name}");
+				}
+			}
 		}
 
 		Ok(())
@@ -54,45 +204,356 @@ impl CommandHandler {
 
 	// --- handlers ---
 	// main
-	fn handle_launch(&self, cmd: &str, query: &str, args: Vec<String>, background: bool) -> Result<()> {
+	fn handle_launch(&self, cmd: &str, query: &str, args: Vec<String>, opts: &LaunchArgs) -> Result<()> {
+		let mut args = args;
+		if opts.args_from_stdin {
+			let mut input = String::new();
+			io::stdin().read_to_string(&mut input)?;
+			args.extend(input.split_whitespace().map(String::from));
+		}
 		let l = self.init_launcher()?;
-		l.launch_app(cmd, query, args, env::vars().collect(), background)
+		l.launch_app(cmd, query, args, env::vars().collect(), &opts.into())
 	}
 
 	// others
-	fn print_app(&self, app: &str, raw: bool) -> Result<()> {
+	fn print_app(
+		&self,
+		app: &str,
+		raw: bool,
+		expand_vars: bool,
+		resolve_runners: bool,
+		resolved: bool,
+		show_path: bool
+	) -> Result<()> {
 		let l = self.init_launcher()?;
-		match terminal_size() {
-			Some((Width(w), _)) if !raw && w >= 40
-			=> println!("{}", l.load_app(app)?),
-			_ => println!("{}", fs::read_to_string(l.find_app(app)?)?),
+		let transformed = expand_vars || resolve_runners;
+
+		// looked up once, an ambiguous name would otherwise ask which app was meant every time
+		let source = l.find_app(app)?;
+		let original = l.load_app_from(source)?;
+		let mut shown = original.clone();
+		if transformed {
+			let resolver = Resolver::new(&l);
+			if resolve_runners {
+				shown = resolver.resolve_runners(source, &original)?;
+			}
+			if expand_vars {
+				shown = resolver.expand_app(&original, &shown)?;
+			}
+		}
+
+		// the same resolution a launch goes through, with secrets masked
+		let resolved_env = if resolved {
+			let parts = l.resolve_source("launch", source, Vec::new(), env::vars().collect(), &ResolveOpts::default())?;
+			Some(parts.env.into_iter()
+				.map(|(k, v)| (k, mask_secrets(&v, &parts.secrets)))
+				.collect::<Map<_, _>>())
+		} else {
+			None
+		};
+
+		if self.json {
+			match resolved_env {
+				Some(env) => println!("{}", serde_json::to_string_pretty(&json!({ "app": shown, "resolved_env": env }))?),
+				None => println!("{}", serde_json::to_string_pretty(&shown)?),
+			}
+			return Ok(());
+		}
+		// tables fall back to 80 columns if the width is unknown (e.g. when piped), and to a plain list on tiny terminals
+		match raw {
+			false => {
+				let mut footer = self.definition_summary(&l, source, &original)?;
+				let counters = LaunchCounter::load_all(&self.config_path);
+				if let Some(counter) = l.name_of(source).and_then(|name| counters.get(name)) {
+					footer = format!(
+						"{footer}\nlaunched {} time(s), last on {}",
+						counter.launch_count,
+						counter.last_launched.format("%Y-%m-%d %H:%M")
+					);
+				}
+				if show_path {
+					footer = format!("{}\n{footer}", self.app_location(source, true));
+				}
+				println!("{}", AppInfo { app: &shown, footer, resolved_env })
+			}
+			_ if transformed => println!("{}", toml::to_string_pretty(&shown)?),
+			_ => match source {
+				AppSource::File(path) => println!("{}", fs::read_to_string(path)?),
+				AppSource::Inline(_) => println!("{}", toml::to_string_pretty(&shown)?),
+			},
+		}
+		Ok(())
+	}
+
+	/// (private) where an app is defined: its file path, or the config file and table for an inline app.
+	/// file paths are made absolute if 'canonical' is set.
+	fn app_location(&self, source: &AppSource, canonical: bool) -> String {
+		let path = match source {
+			AppSource::Inline(name) => return format!("{} [apps.{name}]", self.config_file.display()),
+			AppSource::File(path) if canonical => fs::canonicalize(path).unwrap_or_else(|_| path.clone()),
+			AppSource::File(path) => path.clone(),
+		};
+		path.display().to_string()
+	}
+	/// (private) describes an app's definition ('app', loaded from 'source'): its file's size, line count
+	/// and (if any) how many runner apps its commands go through at most
+	fn definition_summary(&self, l: &Launcher, source: &AppSource, app: &App) -> Result<String> {
+		let resolver = Resolver::new(l);
+		let layers = app.cmds.keys()
+			.filter_map(|cmd| resolver.resolve_command(source, app, cmd).ok())
+			.map(|parts| parts.chain.len())
+			.max()
+			.unwrap_or(0);
+
+		let mut summary = if let AppSource::File(path) = source {
+			let content = fs::read_to_string(path)?;
+			let size = content.len();
+			let size = if size < 1024 {
+				format!("{size} B")
+			} else {
+				format!("{:.1} KiB", size as f64 / 1024.0)
+			};
+			format!("{size}, {} lines", content.lines().count())
+		} else {
+			"defined inline in the config file".to_string()
+		};
+		if layers > 0 {
+			summary.push_str(&format!(", up to {layers} runner layer(s)"));
+		}
+		Ok(summary)
+	}
+
+	/// (private) prints a checklist of problems with the setup, repairing them with 'fix'
+	fn doctor(&self, fix: bool, yes: bool) -> Result<()> {
+		let findings = doctor::diagnose(&self.config_path, &self.config_file);
+		for finding in &findings {
+			println!("{} {}", finding.status, finding.message);
+		}
+
+		let mut broken = findings.iter().filter(|f| f.status == Status::Fail).count();
+		if fix {
+			// the config might be what's broken, so fall back to the default interactivity
+			let interactive = Config::load(&self.config_file)
+				.map(|c| c.noninteractive)
+				.unwrap_or_default()
+				.allowed();
+			for finding in &findings {
+				let Some(repair) = &finding.fix else { continue };
+				let apply = if yes {
+					true
+				} else if interactive {
+					use dialoguer::{theme::ColorfulTheme, Confirm};
+
+					Confirm::with_theme(&ColorfulTheme::default())
+						.with_prompt(format!("{}?", repair.describe()))
+						.default(true)
+						.interact()
+						.unwrap_or(false)
+				} else {
+					bail!("fixing requires confirmation. use -y/--yes or enable interactive mode in your config.");
+				};
+				if apply {
+					repair.apply(&self.config_path, &self.config_file)?;
+					eprintln!("done: {}", repair.describe());
+					if finding.status == Status::Fail {
+						broken -= 1;
+					}
+				}
+			}
+		} else if findings.iter().any(|f| f.fix.is_some()) {
+			eprintln!("run 'ran doctor --fix' to repair what can be repaired automatically.");
+		}
+
+		if broken > 0 {
+			bail!("{broken} check(s) failed");
 		}
 		Ok(())
 	}
+
 	fn print_config(&self, raw: bool) -> Result<()> {
-		match terminal_size() {
-			Some((Width(w), _)) if !raw && w >= 40 => println!("{}", self.init_config()?),
-			_ => println!("{}", fs::read_to_string(self.config_path.join("config.toml"))?),
+		match raw {
+			false => println!("{}", self.init_config()?),
+			true => println!("{}", fs::read_to_string(self.config_file.clone())?),
+		}
+		Ok(())
+	}
+
+	/// (private) moves the definition file at 'old_path' (see Launcher::app_file) to 'new_name'
+	/// (already sanitized), and points aliases that referred to the old name at the new one
+	fn rename_app(&self, l: Launcher, old_path: &Path, new_name: &str) -> Result<()> {
+		let old_name = l.apps.iter()
+			.find(|(_, source)| source.file() == Some(old_path))
+			.map(|(name, _)| name.clone())
+			.ok_or(anyhow!("app definition not found for '{}'", old_path.display()))?;
+		if new_name.is_empty() {
+			bail!("new app name cannot be empty");
+		}
+
+		let new_path = self.config_path.join(format!("apps/{new_name}.toml"));
+		if new_path.exists() {
+			bail!("app '{new_name}' already exists at '{}'", new_path.display());
+		}
+		if let Some(parent) = new_path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		fs::rename(old_path, &new_path)
+			.map_err(|e| anyhow!("failed to move file: {e}"))?;
+		eprintln!("moved '{old_name}' to '{new_name}'");
+
+		// point aliases at the new name
+		let mut c = l.config;
+		let mut updated = 0;
+		for target in c.alias.iter_mut().flat_map(|a| a.values_mut()) {
+			if *target == old_name {
+				*target = new_name.to_string();
+				updated += 1;
+			}
+		}
+		if updated > 0 {
+			c.save(&self.config_file)?;
+			eprintln!("updated {updated} alias(es) pointing to '{old_name}'");
 		}
 		Ok(())
 	}
 
 	fn handle_app_cmd(&self, cmd: AppCmd) -> Result<()> {
 		match cmd {
-			AppCmd::List => {
+			AppCmd::List { tag, sort, format } => {
 				let l = self.init_launcher()?;
-				println!("list of all specified applications");
-				for (name, path) in &l.apps {
+				let mut apps: Vec<(&String, &AppSource)> = match &tag {
+					Some(tag) => l.apps.iter()
+						.filter(|(name, source)| match l.load_app_from(source) {
+							Ok(app) => app.has_tag(tag),
+							Err(e) => {
+								eprintln!("{} skipping '{name}': {e}", "warning:".yellow().bold());
+								false
+							}
+						})
+						.collect(),
+					None => l.apps.iter().collect(),
+				};
+				match sort {
+					ListSort::Name => apps.sort_by(|a, b| a.0.cmp(b.0)),
+					ListSort::Path => apps.sort_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0))),
+					ListSort::Launches => {
+						let counters = LaunchCounter::load_all(&self.config_path);
+						let count = |name: &String| counters.get(name).map_or(0, |c| c.launch_count);
+						apps.sort_by(|a, b| count(b.0).cmp(&count(a.0)).then_with(|| a.0.cmp(b.0)));
+					}
+				}
+				// the global --json wins over --format
+				let format = if self.json { ListFormat::Json } else { format };
+				if let ListFormat::Names = format {
+					for (name, _) in apps {
+						println!("{name}");
+					}
+					return Ok(());
+				}
+				if let ListFormat::Json = format {
+					let list: Vec<_> = apps.iter()
+						.map(|(name, source)| json!({ "name": name, "path": self.app_location(source, false) }))
+						.collect();
+					println!("{}", serde_json::to_string_pretty(&list)?);
+					return Ok(());
+				}
+				for warning in ambiguous_names(l.apps.keys()) {
+					eprintln!("{} {warning}", "warning:".yellow().bold());
+				}
+				match &tag {
+					Some(tag) => eprintln!("list of applications tagged '{tag}'"),
+					None => eprintln!("list of all specified applications"),
+				}
+				for (name, source) in apps {
 					println!(
 						"{} {} {}",
 						name.yellow(),
 						"--".bright_black(),
-						path.to_string_lossy().white()
+						self.app_location(source, false).white()
 					)
 				}
 			}
-			AppCmd::Edit { app } => open_in_editor(self.init_launcher()?.find_app(&app)?, true)?,
-			AppCmd::Print { app, raw } => self.print_app(&app, raw)?,
+			AppCmd::Search { query } => {
+				if query.trim().is_empty() {
+					bail!("search query cannot be empty");
+				}
+				let l = self.init_launcher()?;
+				let results = l.search_apps(&query);
+				if results.is_empty() {
+					eprintln!("no apps matching '{query}'");
+				} else {
+					eprintln!("apps matching '{query}'");
+					for (name, source) in results {
+						println!(
+							"{} {} {}",
+							name.yellow(),
+							"--".bright_black(),
+							self.app_location(source, false).white()
+						)
+					}
+				}
+			}
+			AppCmd::Check { app } => {
+				let l = self.init_launcher()?;
+				let check = l.check_app(&app)?;
+				if self.json {
+					println!("{}", serde_json::to_string_pretty(&json!({
+						"errors": check.errors,
+						"warnings": check.warnings,
+					}))?);
+				} else {
+					for error in &check.errors {
+						println!("{} {error}", "error:".red().bold());
+					}
+					for warning in &check.warnings {
+						println!("{} {warning}", "warning:".yellow().bold());
+					}
+				}
+				if !check.errors.is_empty() {
+					bail!("found {} error(s) in app '{app}'", check.errors.len());
+				}
+				if !self.json && check.warnings.is_empty() {
+					eprintln!("no problems found in app '{app}'");
+				}
+			}
+			AppCmd::Deps { app, command } => {
+				let l = self.init_launcher()?;
+				let source = l.find_app(&app)?;
+				let loaded = l.load_app_from(source)?;
+				// cycles and missing runners fail here, naming the chain up to the problem
+				let parts = Resolver::new(&l).resolve_command(source, &loaded, &command)?;
+				// the app the query (maybe an alias or a leaf name) resolved to
+				let name = l.name_of(source).cloned().unwrap_or(app);
+				if self.json {
+					println!("{}", serde_json::to_string_pretty(&json!({
+						"app": name,
+						"command": command,
+						"runners": parts.chain,
+						"bin": parts.bin,
+					}))?);
+					return Ok(());
+				}
+				let chain = std::iter::once(name.bright_yellow().to_string())
+					.chain(parts.chain.iter().map(|runner| format!("@{runner}").bright_magenta().to_string()))
+					.chain(std::iter::once(parts.bin.white().to_string()))
+					.collect::<Vec<_>>();
+				println!("{}", chain.join(&" -> ".bright_black().to_string()));
+			}
+			AppCmd::Edit { app, force, new } => {
+				let l = self.init_launcher()?;
+				let is_alias = l.config.alias.as_ref().is_some_and(|a| a.contains_key(app.trim()));
+				if new && !is_alias && l.matching_apps(&app).is_empty() {
+					let app_file = new_app(&self.config_path, app, false, None)?;
+					eprintln!("created '{}'", app_file.display());
+					open_in_editor_locked(&app_file, true, force)?
+				} else {
+					// inline apps are edited where they are
+					let path = l.find_app(&app)?.file().unwrap_or(&self.config_file);
+					open_in_editor_locked(path, true, force)?
+				}
+			}
+			AppCmd::Print { app, raw, expand_vars, resolve_runners, resolved, show_path } => {
+				self.print_app(&app, raw, expand_vars, resolve_runners, resolved, show_path)?
+			}
 
 			AppCmd::Get { app, key, raw } => if let Some(key) = key {
 				println!(
@@ -102,30 +563,170 @@ impl CommandHandler {
 						.get(&key).ok_or(anyhow!("invalid key '{key}'"))?
 				);
 			} else {
-				self.print_app(&app, raw)?;
+				self.print_app(&app, raw, false, false, false, false)?;
 			}
 			AppCmd::Set { app, key, value } => {
 				let l = self.init_launcher()?;
-				let app_file = l.find_app(&app)?;
-				let mut app = l.load_app_from(app_file)?;
+				let app_file = l.app_file(&app)?;
+				let mut app = l.load_app_from(&AppSource::File(app_file.to_path_buf()))?;
 				app.set(&key, value)?;
 				app.save(app_file)?;
 			}
 			AppCmd::Unset { app, key } => {
 				let l = self.init_launcher()?;
-				let app_file = l.find_app(&app)?;
-				let mut app = l.load_app_from(app_file)?;
+				let app_file = l.app_file(&app)?;
+				let mut app = l.load_app_from(&AppSource::File(app_file.to_path_buf()))?;
 				app.unset(&key)?;
 				app.save(app_file)?;
 			}
 
-			AppCmd::Create { app, clean, edit } => {
-				let app_file = new_app(&self.config_path, app, clean)?;
+			AppCmd::Rename { from, to } => {
+				let l = self.init_launcher()?;
+				let old_path = l.app_file(&from)?.to_path_buf();
+				self.rename_app(l, &old_path, &sanitize_app_name(&to))?
+			}
+			AppCmd::Move { app, dest } => {
+				let l = self.init_launcher()?;
+				let old_path = l.app_file(&app)?.to_path_buf();
+				let name = l.name_of(&AppSource::File(old_path.clone()))
+					.ok_or(anyhow!("app definition not found for {app}"))?;
+				let leaf = name.rsplit('/').next().unwrap_or(name);
+				let folder = sanitize_app_name(&dest);
+				let new_name = if folder.is_empty() {
+					leaf.to_string()
+				} else {
+					format!("{folder}/{leaf}")
+				};
+				self.rename_app(l, &old_path, &new_name)?;
+			}
+			AppCmd::Copy { src, dest } => {
+				let l = self.init_launcher()?;
+				let src_source = l.find_app(&src)?;
+				let dest_name = sanitize_app_name(&dest);
+				if dest_name.is_empty() {
+					bail!("destination app name cannot be empty");
+				}
+
+				let dest_path = self.config_path.join(format!("apps/{dest_name}.toml"));
+				if dest_path.exists() {
+					bail!("file already exists: {}", dest_path.display());
+				}
+				if let Some(parent) = dest_path.parent() {
+					fs::create_dir_all(parent)?;
+				}
+				match src_source {
+					// copy raw bytes so comments and formatting are preserved
+					AppSource::File(src_path) => {
+						fs::copy(src_path, &dest_path)
+							.map_err(|e| anyhow!("failed to copy file: {e}"))?;
+					}
+					AppSource::Inline(_) => l.load_app_from(src_source)?.save(&dest_path)?,
+				}
+				eprintln!("copied '{}' to '{}'", self.app_location(src_source, false), dest_path.display());
+			}
+			AppCmd::Create { app, clean, template, edit, yes } => {
+				let l = self.init_launcher()?;
+				let similar = l.similar_names(&sanitize_app_name(&app));
+				if !similar.is_empty() && !yes {
+					let similar = similar.join("', '");
+					let create = if l.config.noninteractive.allowed() {
+						use dialoguer::{theme::ColorfulTheme, Confirm};
+
+						Confirm::with_theme(&ColorfulTheme::default())
+							.with_prompt(format!("found similar existing apps/aliases: '{similar}'. continue?"))
+							.default(false)
+							.interact()
+							.unwrap_or(false)
+					} else {
+						// nobody to ask, so it's only worth a warning
+						eprintln!("{} found similar existing apps/aliases: '{similar}'", "warning:".yellow().bold());
+						true
+					};
+					if !create {
+						eprintln!("creation cancelled.");
+						return Ok(());
+					}
+				}
+
+				let app_file = new_app(&self.config_path, app, clean, template.as_deref())?;
 				if edit {
-					open_in_editor(&app_file, true)?;
+					open_in_editor_locked(&app_file, true, false)?;
+				}
+			}
+			AppCmd::Template(TemplateCmd::List) => {
+				let templates = App::find_templates(&self.config_path);
+				if self.json {
+					let list: Vec<_> = templates.iter()
+						.map(|(name, path)| json!({ "name": name, "path": path }))
+						.collect();
+					println!("{}", serde_json::to_string_pretty(&list)?);
+					return Ok(());
+				}
+				if templates.is_empty() {
+					eprintln!("no templates found in '{}'", self.config_path.join("templates").display());
+				} else {
+					eprintln!("list of all app templates");
+					for (name, path) in &templates {
+						println!(
+							"{} {} {}",
+							name.yellow(),
+							"--".bright_black(),
+							path.to_string_lossy().white()
+						)
+					}
 				}
 			}
-			AppCmd::Delete { app, yes } => {
+			AppCmd::Template(TemplateCmd::Save { name, from, force }) => {
+				let l = self.init_launcher()?;
+				let dest = save_template(&self.config_path, name, l.app_file(&from)?, force)?;
+				eprintln!("saved '{from}' as template '{}'", dest.display());
+			}
+			AppCmd::Delete { app, all_matching: true, yes } => {
+				let pattern = sanitize_app_name(&app);
+				// a pattern of only wildcards would silently select everything
+				if !pattern.chars().any(|c| !matches!(c, '*' | '?' | '/')) {
+					bail!("pattern '{app}' would match every app. name at least part of the apps to delete");
+				}
+				let l = self.init_launcher()?;
+				let matches: Vec<(&String, &Path)> = l.apps.iter()
+					.filter_map(|(name, source)| Some((name, source.file()?)))
+					.filter(|(name, _)| glob_match(&pattern, name))
+					.collect();
+				if matches.is_empty() {
+					bail!("no apps match '{app}'");
+				}
+
+				eprintln!("apps matching '{app}':");
+				for (name, path) in &matches {
+					eprintln!("  {} {} {}", name.yellow(), "--".bright_black(), path.display());
+				}
+				let delete = if yes {
+					true
+				} else if l.config.noninteractive.allowed() {
+					use dialoguer::{theme::ColorfulTheme, Confirm};
+
+					Confirm::with_theme(&ColorfulTheme::default())
+						.with_prompt(format!("are you sure you want to delete these {} app(s)?", matches.len()))
+						.default(false)
+						.interact()
+						.unwrap_or(false)
+				} else {
+					bail!(
+						"deletion requires confirmation. use -y/--yes or enable interactive mode in your config."
+					);
+				};
+
+				if delete {
+					for (_, path) in &matches {
+						fs::remove_file(path)
+							.map_err(|e| anyhow!("failed to delete '{}': {e}", path.display()))?;
+					}
+					eprintln!("successfully deleted {} app(s)", matches.len());
+				} else {
+					eprintln!("deletion cancelled.");
+				}
+			}
+			AppCmd::Delete { app, yes, .. } => {
 				let l = self.init_launcher()?;
 				let path = self.config_path.join(format!("apps/{}.toml", sanitize_app_name(&app)));
 
@@ -135,7 +736,7 @@ impl CommandHandler {
 
 				let delete = if yes {
 					true
-				} else if !l.config.noninteractive && atty::is(atty::Stream::Stdout) {
+				} else if l.config.noninteractive.allowed() {
 					use dialoguer::{theme::ColorfulTheme, Confirm};
 
 					Confirm::with_theme(&ColorfulTheme::default())
@@ -152,9 +753,9 @@ impl CommandHandler {
 				if delete {
 					fs::remove_file(&path)
 					.map_err(|e| anyhow!("failed to delete file: {e}"))?;
-					println!("successfully deleted {}", path.display());
+					eprintln!("successfully deleted {}", path.display());
 				} else {
-					println!("deletion cancelled.");
+					eprintln!("deletion cancelled.");
 				}
 			}
 		}
@@ -162,11 +763,67 @@ impl CommandHandler {
 	}
 	fn handle_config_cmd(&self, cmd: ConfigCmd) -> Result<()> {
 		match cmd {
-			ConfigCmd::Path => println!("{}", self.config_path.display()),
-			ConfigCmd::Edit => open_in_editor(&self.config_path.join("config.toml"), true)?,
+			ConfigCmd::Path { apps: false } => println!("{}", self.config_path.display()),
+			ConfigCmd::Path { apps: true } => {
+				let apps_dir = self.config_path.join("apps");
+				fs::create_dir_all(&apps_dir)?;
+				println!("{}", apps_dir.display());
+			}
+			ConfigCmd::Validate => {
+				let config_file = self.config_file.clone();
+				if !config_file.exists() {
+					bail!("config file does not exist in '{}'", config_file.display());
+				}
+				let problems = Config::validate(&config_file)?;
+				if self.json {
+					println!("{}", serde_json::to_string_pretty(&json!({ "errors": problems }))?);
+				} else {
+					for problem in &problems {
+						println!("{} {problem}", "error:".red().bold());
+					}
+				}
+				if !problems.is_empty() {
+					bail!("found {} problem(s) in '{}'", problems.len(), config_file.display());
+				}
+				if !self.json {
+					eprintln!("'{}' looks good", config_file.display());
+				}
+			}
+			ConfigCmd::Edit { force } => {
+				open_in_editor_locked(&self.config_file, true, force)?
+			}
+			ConfigCmd::Editor { test } => {
+				let editor = match preferred_editor() {
+					Some(editor) => {
+						println!("{editor} (from $VISUAL/$EDITOR)");
+						editor
+					}
+					None => {
+						let editor = fallback_editor().to_string();
+						println!("system default opener, falling back to {editor}");
+						editor
+					}
+				};
+				if test {
+					match find_in_path(&editor) {
+						Some(path) => println!("ok: '{editor}' resolves to '{}'", path.display()),
+						None => bail!("editor '{editor}' was not found on PATH"),
+					}
+				}
+			}
 			ConfigCmd::Print { raw } => self.print_config(raw)?,
 
-			ConfigCmd::Get { key, raw } => if let Some(key) = key {
+			ConfigCmd::Get { key: Some(key), raw_toml: true, .. } => {
+				println!("{}", Config::get_raw_toml(&self.config_file, &key)?);
+			}
+			ConfigCmd::Get { key, .. } if self.json => {
+				let key = key.unwrap_or("*".into());
+				let value = self.init_config()?
+					.get_json(&key)
+					.ok_or(anyhow!("invalid key '{key}'"))?;
+				println!("{}", serde_json::to_string_pretty(&value)?);
+			}
+			ConfigCmd::Get { key, raw, .. } => if let Some(key) = key {
 				println!(
 					"{}",
 					self.init_config()?
@@ -177,28 +834,56 @@ impl CommandHandler {
 				self.print_config(raw)?;
 			}
 			ConfigCmd::Set { key, value } => {
+				if key.starts_with("alias.") && !self.init_launcher()?.alias_target_exists(&value) {
+					eprintln!("{} '{value}' is neither an app nor an alias (yet)", "warning:".yellow().bold());
+				}
 				let mut c = self.init_config()?;
 				c.set(&key, value)?;
-				c.save(&self.config_path.join("config.toml"))?;
+				c.save(&self.config_file)?;
 			}
 			ConfigCmd::Unset { key } => {
 				let mut c = self.init_config()?;
 				c.unset(&key)?;
-				c.save(&self.config_path.join("config.toml"))?;
+				c.save(&self.config_file)?;
 			}
 
+			ConfigCmd::Reset { yes } => {
+				// a mangled config can't tell whether prompts are allowed, so its default is assumed then
+				let interactivity = Config::load(&self.config_file)
+					.map(|c| c.noninteractive)
+					.unwrap_or_default();
+				let reset = if yes {
+					true
+				} else if interactivity.allowed() {
+					use dialoguer::{theme::ColorfulTheme, Confirm};
+
+					Confirm::with_theme(&ColorfulTheme::default())
+						.with_prompt(format!("replace '{}' with the default config?", self.config_file.display()))
+						.default(false)
+						.interact()
+						.unwrap_or(false)
+				} else {
+					bail!("resetting the config requires confirmation. use -y/--yes or enable interactive mode in your config.");
+				};
+				if reset {
+					doctor::Fix::RecreateConfig.apply(&self.config_path, &self.config_file)?;
+					eprintln!("reset '{}' to the default config", self.config_file.display());
+				} else {
+					eprintln!("reset cancelled.");
+				}
+			}
 			ConfigCmd::Init { yes, clean, edit } => {
-				let config_file = self.config_path.join("config.toml");
+				let config_file = self.config_file.clone();
 				if config_file.exists() {
-					println!("a config file already exist in '{}'", config_file.display());
+					eprintln!("a config file already exist in '{}'", config_file.display());
 					let c = self.init_config()?;
 					let delete = if yes {
 						true
-					} else if !c.noninteractive && atty::is(atty::Stream::Stdout) {
+					} else if c.noninteractive.allowed() {
 						use dialoguer::{theme::ColorfulTheme, Confirm};
 
 						Confirm::with_theme(&ColorfulTheme::default())
-							.with_prompt(format!("do you want to delete the existing config and reinitialize?"))
+							.with_prompt("do you want to delete the existing config and reinitialize?")
 							.default(false)
 							.interact()
 							.unwrap_or(false)
@@ -210,15 +895,15 @@ impl CommandHandler {
 					if delete {
 						fs::remove_file(&config_file)
 						.map_err(|e| anyhow!("failed to delete file: {e}"))?;
-						println!("successfully deleted {}", config_file.display());
+						eprintln!("successfully deleted {}", config_file.display());
 					} else {
-						println!("re-initialization cancelled.");
+						eprintln!("re-initialization cancelled.");
 					}
 				}
 				new_config_file(&config_file, clean)?;
-				println!("initalized config file in '{}'", config_file.display());
+				eprintln!("initalized config file in '{}'", config_file.display());
 				if edit {
-					open_in_editor(&config_file, true)?;
+					open_in_editor_locked(&config_file, true, false)?;
 				}
 			}
 		}
@@ -226,13 +911,35 @@ impl CommandHandler {
 	}
 	fn handle_alias_cmd(&self, cmd: AliasCmd) -> Result<()> {
 		match cmd {
-			AliasCmd::List => {
+			AliasCmd::List { tree: true } => {
+				let l = self.init_launcher()?;
+				let resolver = Resolver::new(&l);
+				// every app (or dead end) an alias chain ends at, in the order the aliases are defined
+				let mut roots: Vec<String> = Vec::new();
+				for name in l.config.alias.iter().flat_map(|alias| alias.keys()) {
+					if let Ok(chain) = resolver.resolve_alias_chain(name.trim())
+						&& let Some(root) = chain.last()
+						&& !roots.contains(root)
+					{
+						roots.push(root.clone());
+					}
+				}
+				if roots.is_empty() {
+					eprintln!("no app aliases were defined");
+				}
+				for root in roots {
+					for line in alias_tree(&l, &root) {
+						println!("{line}");
+					}
+				}
+			}
+			AliasCmd::List { .. } => {
 				let l = self.init_launcher()?;
 				let resolver = Resolver::new(&l);
 				if let Some(alias) = &l.config.alias {
-					println!("list of all specified app aliases");
+					eprintln!("list of all specified app aliases");
 					for name in alias.keys() {
-						let chain_result = resolver.resolve_alias_chain(&name.trim());
+						let chain_result = resolver.resolve_alias_chain(name.trim());
 						let pretty_chain = match chain_result {
 							Ok(chain) => {
 								// if there’s no chain, just print the key
@@ -248,8 +955,12 @@ impl CommandHandler {
 												// first in chain gets bold magenta
 												item.bright_magenta().bold().to_string()
 											} else if i == len - 1 {
-												// last in chain gets yellow
-												item.bright_yellow().to_string()
+												// last in chain gets yellow, or red if there's no such app
+												if l.matching_apps(&item).is_empty() {
+													item.bright_red().to_string()
+												} else {
+													item.bright_yellow().to_string()
+												}
 											} else {
 												item.bright_magenta().bold().to_string()
 											}
@@ -261,19 +972,27 @@ impl CommandHandler {
 							Err(e) => {
 								format!(
 									"{} -> {e}",
-									name.bright_magenta().bold().to_string()
-								).to_string()
+									name.bright_magenta().bold()
+								)
 							}
 						};
 						println!("{pretty_chain}");
 					}
 				} else {
-					println!("no app aliases were defined");
+					eprintln!("no app aliases were defined");
 				}
 			}
 
-			AliasCmd::Get { key, unresolved } => if !unresolved {
+			AliasCmd::Get { key, tree: true, .. } => {
 				let l = self.init_launcher()?;
+				let chain = Resolver::new(&l).resolve_alias_chain(key.trim())?;
+				let root = chain.last().ok_or(anyhow!("undefined app alias '{key}'"))?;
+				for line in alias_tree(&l, root) {
+					println!("{line}");
+				}
+			}
+			AliasCmd::Get { key, unresolved, .. } => if !unresolved {
+				let l = self.init_config_launcher()?;
 				let resolver = Resolver::new(&l);
 				let chain_result = resolver.resolve_alias_chain(&key);
 				let pretty_chain = match chain_result {
@@ -316,15 +1035,57 @@ impl CommandHandler {
 						.ok_or(anyhow!("undefined app alias '{key}'"))?
 				);
 			}
-			AliasCmd::Set { key, value } => {
-				let mut c = self.init_config()?;
+			AliasCmd::Set { key, value, strict } => {
+				let l = self.init_launcher()?;
+				if !l.alias_target_exists(&value) {
+					if strict {
+						bail!("'{value}' is neither an app nor an alias");
+					}
+					eprintln!("{} '{value}' is neither an app nor an alias (yet)", "warning:".yellow().bold());
+				}
+				let mut c = l.config;
 				c.set(&format!("alias.{key}"), value)?;
-				c.save(&self.config_path.join("config.toml"))?;
+				c.save(&self.config_file)?;
 			}
 			AliasCmd::Unset { key } => {
 				let mut c = self.init_config()?;
 				c.unset(&format!("alias.{key}"))?;
-				c.save(&self.config_path.join("config.toml"))?;
+				c.save(&self.config_file)?;
+			}
+			AliasCmd::Rename { from, to } => {
+				let (from, to) = (from.trim(), to.trim());
+				if to.is_empty() {
+					bail!("alias name cannot be empty");
+				}
+				let mut c = self.init_config()?;
+				let alias = c.alias.take().unwrap_or_default();
+				let target = alias.get(from).ok_or(anyhow!("undefined app alias '{from}'"))?;
+				if alias.contains_key(to) {
+					bail!("alias '{to}' already exists");
+				}
+				if target.trim().trim_matches('/') == to {
+					bail!("renaming '{from}' to '{to}' would make it point at itself");
+				}
+
+				// rename in place, and point aliases that referred to the old name at the new one
+				let mut updated = 0;
+				c.alias = Some(alias.into_iter()
+					.map(|(k, v)| {
+						let k = if k == from { to.to_string() } else { k };
+						let v = if v.trim().trim_matches('/') == from {
+							updated += 1;
+							to.to_string()
+						} else {
+							v
+						};
+						(k, v)
+					})
+					.collect());
+				c.save(&self.config_file)?;
+				eprintln!("renamed alias '{from}' to '{to}'");
+				if updated > 0 {
+					eprintln!("updated {updated} alias(es) pointing to '{from}'");
+				}
 			}
 		}
 		Ok(())
@@ -334,16 +1095,16 @@ impl CommandHandler {
 			VarCmd::List => {
 				let c = self.init_config()?;
 				if let Some(vars) = &c.vars {
-					println!("list of all specified global variables");
+					eprintln!("list of all specified global variables");
 					for (key, value) in vars {
 						println!(
 							"{} {} {value}",
 							format!("${key}").bright_red(),
-							format!("=").bright_black()
+							"=".bright_black()
 						);
 					}
 				} else {
-					println!("no app aliases were defined");
+					eprintln!("no app aliases were defined");
 				}
 			}
 
@@ -356,14 +1117,57 @@ impl CommandHandler {
 			VarCmd::Set { key, value } => {
 				let mut c = self.init_config()?;
 				c.set(&format!("vars.{key}"), value)?;
-				c.save(&self.config_path.join("config.toml"))?;
+				c.save(&self.config_file)?;
 			}
 			VarCmd::Unset { key } => {
 				let mut c = self.init_config()?;
 				c.unset(&format!("vars.{key}"))?;
-				c.save(&self.config_path.join("config.toml"))?;
+				c.save(&self.config_file)?;
 			}
 		}
 		Ok(())
 	}
 }
+
+// --- functions ---
+/// (private) splits the app name off a `launch`/`cmd` command line, the rest are the app's args
+/// (clap makes sure there's a name)
+fn split_app_args(mut app: Vec<String>) -> (String, Vec<String>) {
+	let name = app.remove(0);
+	(name, app)
+}
+
+/// (private) draws the aliases leading to 'root' as a tree, with 'root' annotated with the
+/// definition file it resolves to (the apps it could mean if it's ambiguous, or marked red if there's no such app)
+fn alias_tree(l: &Launcher, root: &str) -> Vec<String> {
+	fn branches(l: &Launcher, target: &str, prefix: &str, seen: &mut Vec<String>, lines: &mut Vec<String>) {
+		let children: Vec<&String> = l.config.alias.iter()
+			.flat_map(|alias| alias.iter())
+			.filter(|(name, value)| value.trim() == target && !seen.contains(name))
+			.map(|(name, _)| name)
+			.collect();
+		for (i, child) in children.iter().enumerate() {
+			let last = i == children.len() - 1;
+			let branch = if last { "└── " } else { "├── " };
+			lines.push(format!("{prefix}{}{}", branch.bright_black(), child.bright_magenta().bold()));
+			seen.push(child.to_string());
+			let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+			branches(l, child, &prefix, seen, lines);
+		}
+	}
+
+	// never asks which app an ambiguous name means, the tree just says so
+	let head = match l.matching_apps(root).as_slice() {
+		[] => format!("{} {}", root.bright_red(), "(no such app)".bright_black()),
+		[source] => format!("{} {}", root.bright_yellow(), format!("({source})").bright_black()),
+		sources => {
+			let names: Vec<&str> = sources.iter()
+				.filter_map(|source| l.name_of(source).map(String::as_str))
+				.collect();
+			format!("{} {}", root.bright_yellow(), format!("(ambiguous: {})", names.join(", ")).bright_black())
+		}
+	};
+	let mut lines = vec![head];
+	branches(l, root, "", &mut vec![root.to_string()], &mut lines);
+	lines
+}
@@ -0,0 +1,215 @@
+// --- constants ---
+pub const HISTORY_FILE: &str = "history.jsonl";
+pub const DEFAULT_HISTORY_SIZE: usize = 500;
+pub const COUNTERS_FILE: &str = "stats.toml";
+/// the columns of `ran history --csv`
+pub const CSV_HEADER: &str = "timestamp,app,args,background,exit_code";
+/// no history line is shorter than this (the keys and timestamp alone take up more)
+const MIN_ENTRY_SIZE: u64 = 90;
+
+// --- imports ---
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use indexmap::IndexMap as Map;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+// --- definitions ---
+/// a single launch, stored as one json line in config_path/history.jsonl.
+/// foreground launches are recorded once the app exits, background ones once they've started.
+#[derive(Deserialize, Serialize)]
+pub struct HistoryEntry {
+	/// when the app was started
+	pub timestamp: DateTime<Local>,
+	pub app: String,
+	pub cmd: String,
+	pub bin: String,
+	pub args: Vec<String>,
+	pub background: bool,
+	/// how a foreground app exited. none for background launches, apps ran detached from
+	/// (`detach_after`) and apps killed by a signal
+	#[serde(default)]
+	pub exit_code: Option<i32>,
+}
+
+/// how often an app was launched, stored per app name in config_path/stats.toml.
+/// unlike the history it's never trimmed (see `app info` and `app list --sort launches`).
+#[derive(Deserialize, Serialize)]
+pub struct LaunchCounter {
+	pub launch_count: u64,
+	pub last_launched: DateTime<Local>,
+}
+
+// --- implementations ---
+impl HistoryEntry {
+	/// appends this entry to the history file. once it holds more than 1.5 × 'max' entries, it's trimmed
+	/// back to the newest 'max', so the file is only rewritten every max / 2 launches. a 'max' of 0 disables history.
+	pub fn record(&self, config_path: &Path, max: usize) -> Result<()> {
+		if max == 0 {
+			return Ok(());
+		}
+		let file = config_path.join(HISTORY_FILE);
+
+		let mut out = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&file)
+			.with_context(|| format!("failed to open history file '{}'", file.display()))?;
+		writeln!(out, "{}", serde_json::to_string(self)?)?;
+		let size = out.metadata()?.len();
+		drop(out);
+
+		// trim. files too small to hold 'limit' entries aren't even read
+		let limit = max + max / 2;
+		if size <= limit as u64 * MIN_ENTRY_SIZE {
+			return Ok(());
+		}
+		let content = fs::read_to_string(&file)?;
+		let lines: Vec<&str> = content.lines().collect();
+		if lines.len() > limit {
+			let tmp = file.with_extension("jsonl.tmp");
+			fs::write(&tmp, lines[lines.len() - max..].join("\n") + "\n")?;
+			fs::rename(&tmp, &file)?;
+		}
+		Ok(())
+	}
+
+	/// this entry as a line of `ran history --csv` (see CSV_HEADER). the args are joined like a shell
+	/// would need them, and fields with commas, quotes or line breaks are quoted
+	pub fn to_csv(&self) -> String {
+		let fields = [
+			self.timestamp.to_rfc3339(),
+			self.app.clone(),
+			shell_words::join(&self.args),
+			self.background.to_string(),
+			self.exit_code.map(|code| code.to_string()).unwrap_or_default(),
+		];
+		fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",")
+	}
+
+	/// loads all history entries, oldest first (skipping lines that can't be parsed)
+	pub fn load_all(config_path: &Path) -> Result<Vec<HistoryEntry>> {
+		let file = config_path.join(HISTORY_FILE);
+		if !file.exists() {
+			return Ok(Vec::new());
+		}
+		let content = fs::read_to_string(&file)
+			.with_context(|| format!("failed to read history file '{}'", file.display()))?;
+		Ok(content
+			.lines()
+			.filter_map(|line| serde_json::from_str(line).ok())
+			.collect())
+	}
+}
+
+impl LaunchCounter {
+	/// loads every app's counter (none if there's no counter file yet, or it can't be parsed)
+	pub fn load_all(config_path: &Path) -> Map<String, LaunchCounter> {
+		fs::read_to_string(config_path.join(COUNTERS_FILE))
+			.ok()
+			.and_then(|text| toml::from_str(&text).ok())
+			.unwrap_or_default()
+	}
+
+	/// counts a launch of 'app'. the file is written to a temporary file first and then renamed over
+	/// the old one, so concurrent launches can't leave it half-written (one of their counts may be lost).
+	pub fn record(config_path: &Path, app: &str) -> Result<()> {
+		let mut counters = Self::load_all(config_path);
+		let counter = counters.entry(app.to_string()).or_insert(LaunchCounter {
+			launch_count: 0,
+			last_launched: Local::now(),
+		});
+		counter.launch_count += 1;
+		counter.last_launched = Local::now();
+
+		let file = config_path.join(COUNTERS_FILE);
+		let tmp = config_path.join(format!("{COUNTERS_FILE}.{}.tmp", std::process::id()));
+		fs::write(&tmp, toml::to_string(&counters)?)
+			.with_context(|| format!("failed to write '{}'", tmp.display()))?;
+		fs::rename(&tmp, &file)
+			.with_context(|| format!("failed to replace '{}'", file.display()))?;
+		Ok(())
+	}
+}
+
+// --- functions ---
+/// (private) quotes a csv field (doubling the quotes in it) if it contains a comma, quote or line break
+fn csv_field(field: &str) -> Cow<'_, str> {
+	if field.contains([',', '"', '\n', '\r']) {
+		Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+	} else {
+		Cow::Borrowed(field)
+	}
+}
+
+// --- tests ---
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entry(app: &str) -> HistoryEntry {
+		HistoryEntry {
+			timestamp: Local::now(),
+			app: app.into(),
+			cmd: "launch".into(),
+			bin: String::new(),
+			args: Vec::new(),
+			background: false,
+			exit_code: None,
+		}
+	}
+
+	#[test]
+	fn entries_are_never_shorter_than_the_minimum_size() {
+		let line = serde_json::to_string(&entry("")).unwrap();
+		assert!(line.len() as u64 + 1 >= MIN_ENTRY_SIZE, "{line}");
+	}
+
+	#[test]
+	fn trims_with_slack() {
+		let dir = tempfile::tempdir().unwrap();
+		let apps = |dir: &Path| -> Vec<String> {
+			HistoryEntry::load_all(dir).unwrap().into_iter().map(|entry| entry.app).collect()
+		};
+
+		// up to 1.5 × max entries are kept as they are
+		for i in 0..15 {
+			entry(&i.to_string()).record(dir.path(), 10).unwrap();
+		}
+		assert_eq!(apps(dir.path()).len(), 15);
+
+		// one more, and it's trimmed back to the newest 'max'
+		entry("15").record(dir.path(), 10).unwrap();
+		let expected: Vec<String> = (6..16).map(|i| i.to_string()).collect();
+		assert_eq!(apps(dir.path()), expected);
+	}
+
+	#[test]
+	fn csv_quotes_fields_that_need_it() {
+		let entry = HistoryEntry {
+			args: vec!["--name".into(), "a, b".into(), "say \"hi\"".into()],
+			exit_code: Some(3),
+			..entry("games/doom")
+		};
+		let row = entry.to_csv();
+		let timestamp = entry.timestamp.to_rfc3339();
+		assert_eq!(row, format!(r#"{timestamp},games/doom,"--name 'a, b' 'say ""hi""'",false,3"#));
+		assert_eq!(CSV_HEADER.split(',').count(), 5);
+
+		// loaded from an old history without exit codes
+		let old: HistoryEntry = serde_json::from_str(
+			r#"{"timestamp":"2026-01-31T21:45:09+00:00","app":"x","cmd":"launch","bin":"x","args":[],"background":true}"#
+		).unwrap();
+		assert_eq!(old.to_csv(), format!("{},x,,true,", old.timestamp.to_rfc3339()));
+	}
+
+	#[test]
+	fn zero_disables_history() {
+		let dir = tempfile::tempdir().unwrap();
+		entry("app").record(dir.path(), 0).unwrap();
+		assert!(!dir.path().join(HISTORY_FILE).exists());
+	}
+}
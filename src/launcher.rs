@@ -1,35 +1,170 @@
 // --- imports ---
 use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
 use indexmap::IndexMap as Map;
+use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use crate::app::App;
+use std::fmt::{self, Display, Formatter};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::app::{App, DEFAULT_RETRY_DELAY_MS};
 use crate::config::Config;
-use crate::resolver::Resolver;
+use crate::history::{HistoryEntry, LaunchCounter, DEFAULT_HISTORY_SIZE};
+use crate::resolver::{ResolvedParts, Resolver};
 use crate::util::args::sandwich_args;
+use crate::util::fs::{find_in_path, find_in_search_path, read_env_file};
+use crate::util::log::{self, verbose};
+use crate::util::search::{fuzzy_score, is_similar, levenshtein};
+use crate::util::signal::{SignalForwarder, TerminalHandoff};
 
 // --- definitions ---
 pub struct Launcher {
-	pub apps: Map<String, PathBuf>,
+	pub config_path: PathBuf,
+	/// every app's full name and where it's defined
+	pub apps: Map<String, AppSource>,
 	pub config: Config,
 }
 
+/// where an app is defined. files sort before inline apps
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AppSource {
+	/// a definition file of its own, in config_path/apps
+	File(PathBuf),
+	/// an `[apps.<name>]` table in the config file, by name
+	Inline(String),
+}
+
+/// what changes how an app is resolved, besides its definition and the config (see Launcher::resolve).
+/// `ran launch` and friends fill these in from their command line options.
+#[derive(Clone, Default)]
+pub struct ResolveOpts {
+	/// env file (KEY=VALUE per line) layered between the config's and the app's env, instead of config_path/launch.env
+	pub user_env: Option<PathBuf>,
+	/// env overriding every other layer (`--env`)
+	pub env: Vec<(String, String)>,
+	/// start from no env at all instead of the given one
+	pub clean_env: bool,
+	/// working directory instead of the app's `working_dir`
+	pub cwd: Option<PathBuf>,
+}
+
+/// how Launcher::launch_app and Launcher::launch_group run an app, on top of ResolveOpts.
+/// `ran launch` and friends fill these in from their command line options
+#[derive(Clone, Default)]
+pub struct LaunchOpts {
+	/// run in the background (in a new terminal if `terminal_runner` is set), detached from ran's terminal
+	pub background: bool,
+	/// run in the foreground, even if the app has `background = true`
+	pub foreground: bool,
+	/// env file layered between the config's and the app's env, instead of config_path/launch.env
+	pub user_env: Option<PathBuf>,
+	/// env overriding every other layer
+	pub env: Vec<(String, String)>,
+	/// don't inherit the given env, the app (and its hooks) only get the env ran layers on top
+	pub clean_env: bool,
+	/// working directory instead of the app's `working_dir`
+	pub cwd: Option<PathBuf>,
+	/// don't print ran's own status lines
+	pub quiet: bool,
+	/// kill a foreground app (and on unix, everything it started) after this many seconds
+	pub timeout: Option<u64>,
+	/// fail on the first error exit, even if the app sets `retries`
+	pub no_retry: bool,
+	/// discard the app's stdout/stderr while still waiting for it to exit
+	pub null_io: bool,
+	/// print (to stderr) the env variables ran adds or changes before launching
+	pub print_env: bool,
+	/// print the resolved executable, arguments and env instead of running anything
+	pub dry_run: bool,
+}
+
+/// errors of a launch that callers may want to tell apart, e.g. for the exit status. all other errors
+/// are plain anyhow errors with a message
+#[derive(Debug)]
+pub enum LauncherError {
+	/// a foreground launch ran longer than `--timeout` and was killed
+	TimedOut {
+		app: String,
+		secs: u64,
+	},
+	/// a foreground launch was signalled to stop (e.g. ctrl-c), after the app exited
+	Interrupted {
+		app: String,
+		signal: i32,
+	},
+	/// a runner chain leads back to an app (and command) already in it. holds the chain from the
+	/// resolved app up to and including the repeated one
+	CircularRunner(Vec<String>),
+}
+
+/// problems found in an app definition by Launcher::check_app
+#[derive(Default)]
+pub struct AppCheck {
+	/// problems that would make a launch fail
+	pub errors: Vec<String>,
+	/// things that are probably mistakes, but don't stop a launch
+	pub warnings: Vec<String>,
+}
+
 // --- implementations ---
+impl From<&LaunchOpts> for ResolveOpts {
+	fn from(opts: &LaunchOpts) -> Self {
+		ResolveOpts {
+			user_env: opts.user_env.clone(),
+			env: opts.env.clone(),
+			clean_env: opts.clean_env,
+			cwd: opts.cwd.clone(),
+		}
+	}
+}
+
+impl AppSource {
+	/// the app's definition file, if it isn't defined inline
+	pub fn file(&self) -> Option<&Path> {
+		match self {
+			AppSource::File(path) => Some(path),
+			AppSource::Inline(_) => None,
+		}
+	}
+}
+
+impl Display for AppSource {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			AppSource::File(path) => write!(f, "{}", path.display()),
+			AppSource::Inline(name) => write!(f, "[apps.{name}] in the config file"),
+		}
+	}
+}
+
+impl Display for LauncherError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			LauncherError::TimedOut { app, secs } => write!(f, "app '{app}' timed out after {secs}s and was killed"),
+			LauncherError::Interrupted { app, signal } => write!(f, "app '{app}' was interrupted (signal {signal})"),
+			LauncherError::CircularRunner(chain) => write!(f, "infinite recursion in runner chain: {}", chain.join(" -> ")),
+		}
+	}
+}
+
+impl std::error::Error for LauncherError {}
+
 impl Launcher {
 	/// interactively resolve app name conflicts
-	pub fn conflict_resolver<'p>(
+	pub fn conflict_resolver<'s>(
 		&self,
 		query: &str,
-		matches: Vec<&'p Path>
-	) -> Result<&'p Path> {
+		matches: Vec<&'s AppSource>
+	) -> Result<&'s AppSource> {
 		// check if we are allowed to be interactive
-		if self.config.noninteractive || !atty::is(atty::Stream::Stdout) {
+		if !self.config.noninteractive.allowed() {
 			bail!(
 				"multiple results for query '{query}': {}",
 				matches
 				.iter()
-				.map(|&p| p.to_string_lossy().into_owned())
+				.map(|source| source.to_string())
 				.collect::<Vec<String>>()
 				.join(", ")
 			);
@@ -40,7 +175,7 @@ impl Launcher {
 
 		let items: Vec<String> = matches
 		.iter()
-		.map(|p| p.to_string_lossy().to_string())
+		.map(|source| source.to_string())
 		.collect();
 
 		let selection = Select::with_theme(&ColorfulTheme::default())
@@ -56,7 +191,7 @@ impl Launcher {
 	}
 
 	/// (private) finds app from query with stack tracking
-	fn find_app_inner(&self, query: &str, stack: Vec<String>) -> Result<&Path> {
+	fn find_app_inner(&self, query: &str, stack: Vec<String>) -> Result<&AppSource> {
 		if stack.contains(&query.into()) {
 			let mut stack = stack;
 			stack.push(query.into());
@@ -65,48 +200,123 @@ impl Launcher {
 		let query = query.trim().trim_matches('/');
 		if query.is_empty() { bail!("app definition not found for '{query}'") }
 
-		if let Some(alias) = &self.config.alias {
-			if let Some(app) = alias.get(query) {
-				let mut stack = stack;
-				stack.push(query.to_string());
-				return self.find_app_inner(app, stack);
-			}
+		if let Some(app) = self.config.alias.as_ref().and_then(|a| a.get(query)) {
+			let mut stack = stack;
+			stack.push(query.to_string());
+			return self.find_app_inner(app, stack);
 		}
 
-		let matches: Vec<&Path> = self.apps.iter()
-		.filter(|(full_name, _)| {
-			let leaf_name = full_name.split('/').last().unwrap_or(full_name);
-			full_name == &query || leaf_name == query
-		})
-		.map(|(_, path)| path.as_path())
-		.collect();
+		let matches = self.matching_apps(query);
 
-		if matches.len() > 0 {
+		if !matches.is_empty() {
 			match matches.len() {
-				1 => Ok(matches.get(0).ok_or(anyhow!("app definition not found for {query}"))?),
+				1 => Ok(matches.first().ok_or(anyhow!("app definition not found for {query}"))?),
 				_ => Ok(self.conflict_resolver(query, matches)?)
 			}
 		} else {
-			bail!("app definition not found for {query}");
+			bail!("app definition not found for {query}{}", self.suggestion(query));
 		}
 	}
 
+	/// (private) a ", did you mean '...'?" hint naming the app or alias closest to 'query', if
+	/// one is close enough (compared by full and leaf name)
+	fn suggestion(&self, query: &str) -> String {
+		let query = query.to_lowercase();
+		let aliases = self.config.alias.iter().flat_map(|a| a.keys());
+		self.apps.keys()
+			.chain(aliases)
+			.map(|name| {
+				let lower = name.to_lowercase();
+				let leaf = lower.rsplit('/').next().unwrap_or(&lower);
+				(levenshtein(&query, &lower).min(levenshtein(&query, leaf)), name)
+			})
+			.min()
+			.filter(|(distance, _)| *distance <= 2 && *distance < query.chars().count())
+			.map(|(_, name)| format!(", did you mean '{name}'?"))
+			.unwrap_or_default()
+	}
+
+	/// returns the definitions of all apps whose full or leaf name is 'query' (aliases aren't resolved)
+	pub fn matching_apps(&self, query: &str) -> Vec<&AppSource> {
+		let query = query.trim().trim_matches('/');
+		self.apps.iter()
+			.filter(|(full_name, _)| {
+				let leaf_name = full_name.split('/').next_back().unwrap_or(full_name);
+				full_name == &query || leaf_name == query
+			})
+			.map(|(_, source)| source)
+			.collect()
+	}
+
+	/// whether an alias pointing at 'target' would lead somewhere, i.e. 'target' is another alias
+	/// or the name of an existing app. doesn't prompt on ambiguous names like find_app does.
+	pub fn alias_target_exists(&self, target: &str) -> bool {
+		let target = target.trim().trim_matches('/');
+		self.config.alias.as_ref().is_some_and(|a| a.contains_key(target))
+			|| !self.matching_apps(target).is_empty()
+	}
+
 	/// finds app from query, resolving aliases, and errors on circular references
-	pub fn find_app(&self, query: &str) -> Result<&Path> {
+	pub fn find_app(&self, query: &str) -> Result<&AppSource> {
 		self.find_app_inner(query, vec![])
 	}
 
+	/// finds the definition file of an app like find_app, but errors if it's defined inline in the
+	/// config (for commands that change or move the file)
+	pub fn app_file(&self, query: &str) -> Result<&Path> {
+		match self.find_app(query)? {
+			AppSource::File(path) => Ok(path),
+			AppSource::Inline(name) => bail!(
+				"app '{name}' is defined inline in the config file, change it with `ran config edit` \
+				(or turn it into a file with `ran app copy {name} <new name>`)"
+			),
+		}
+	}
+
+	/// returns the full name of the app defined at 'source'
+	pub fn name_of(&self, source: &AppSource) -> Option<&String> {
+		self.apps.iter().find(|(_, s)| *s == source).map(|(n, _)| n)
+	}
+
+	/// finds apps whose names fuzzily match the query, best matches first
+	pub fn search_apps(&self, query: &str) -> Vec<(&String, &AppSource)> {
+		let mut results: Vec<(usize, &String, &AppSource)> = self.apps.iter()
+			.filter_map(|(name, source)| fuzzy_score(query, name).map(|score| (score, name, source)))
+			.collect();
+		results.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+		results.into_iter().map(|(_, name, source)| (name, source)).collect()
+	}
+
+	/// finds app names and aliases that look similar to 'name' (but aren't exactly it)
+	pub fn similar_names(&self, name: &str) -> Vec<String> {
+		let aliases = self.config.alias.iter().flat_map(|a| a.keys());
+		self.apps.keys()
+			.chain(aliases)
+			.filter(|other| *other != name && is_similar(name, other))
+			.cloned()
+			.collect()
+	}
+
 	/// loads app from query, resolving aliases, and errors on circular references
 	pub fn load_app(&self, query: &str) -> Result<App> {
-		let path = self.find_app(query)?;
-		let content = fs::read_to_string(path)?;
-		Ok(toml::from_str(&content)?)
+		self.load_app_from(self.find_app(query)?)
 	}
 
-	/// loads app from path, without resolving aliases
-	pub fn load_app_from(&self, path: &Path) -> Result<App> {
-		let content = fs::read_to_string(path)?;
-		Ok(toml::from_str(&content)?)
+	/// loads app from where it's defined, without resolving aliases. parse errors name the file and the
+	/// line and column of the problem.
+	pub fn load_app_from(&self, source: &AppSource) -> Result<App> {
+		let path = match source {
+			AppSource::File(path) => path,
+			AppSource::Inline(name) => {
+				let table = self.config.apps.as_ref().and_then(|apps| apps.get(name)).cloned().unwrap_or_default();
+				return toml::Value::Table(table).try_into()
+					.map_err(|e| anyhow!("invalid inline app '{name}' in the config file: {}", e.message()));
+			}
+		};
+		let content = fs::read_to_string(path)
+			.map_err(|e| anyhow!("failed to read '{}': {e}", path.display()))?;
+		toml::from_str(&content)
+			.map_err(|e| anyhow!("invalid app definition '{}': {e}", path.display()))
 	}
 
 	/// initializes launcher by scanning for apps and loading config
@@ -115,47 +325,160 @@ impl Launcher {
 		if !app_path.exists() {
 			fs::create_dir_all(app_path)?;
 		}
-		let apps = App::find_all(config_path);
+		let mut apps: Map<String, AppSource> = App::find_all(config_path)
+			.into_iter()
+			.map(|(name, path)| (name, AppSource::File(path)))
+			.collect();
+		// app files take precedence over inline apps of the same name
+		for name in config.apps.iter().flat_map(|a| a.keys()) {
+			match apps.get(name) {
+				Some(source) => eprintln!(
+					"warning: app '{name}' is defined both inline in the config file and in '{source}', using the file"
+				),
+				None => {
+					apps.insert(name.clone(), AppSource::Inline(name.clone()));
+				}
+			}
+		}
+		apps.sort_keys();
 		Ok(Launcher {
+			config_path: config_path.to_path_buf(),
 			apps,
 			config,
 		})
 	}
 
-	/// launch an app by query with a specified command, with cli args and env, resolving aliases, and errors on circular references
-	pub fn launch_app(
+	/// every app name, from app files and inline `[apps.<name>]` tables, sorted. unlike init this never
+	/// creates anything, and a config file that can't be loaded only leaves out the inline apps.
+	pub fn app_names(config_path: &Path, config_file: &Path) -> Vec<String> {
+		let mut names: Vec<String> = App::find_all(config_path).into_keys().collect();
+		if let Ok(config) = Config::load(config_file) {
+			names.extend(config.apps.into_iter().flat_map(|apps| apps.into_keys()));
+		}
+		names.sort();
+		names.dedup();
+		names
+	}
+
+	/// initializes a launcher with only the config loaded, without scanning for apps.
+	/// for commands that never look up apps (e.g. resolving alias chains), as app lookups will find nothing.
+	pub fn init_config_only(config_path: &Path, config: Config) -> Launcher {
+		Launcher {
+			config_path: config_path.to_path_buf(),
+			apps: Map::new(),
+			config,
+		}
+	}
+
+	/// loads the user env file (`--user-env`, or config_path/launch.env if it exists)
+	pub fn load_user_env(&self, file: Option<&Path>) -> Result<Map<String, String>> {
+		match file {
+			Some(file) => read_env_file(file),
+			None => {
+				let default = self.config_path.join("launch.env");
+				if default.exists() {
+					read_env_file(&default)
+				} else {
+					Ok(Map::new())
+				}
+			}
+		}
+	}
+
+	/// resolves everything needed to launch an app by query with a specified command (executable,
+	/// args, env, working directory and runner chain) without spawning anything.
+	/// all variables in the result are expanded, and `chain` starts with the app's full name.
+	///
+	/// env precedence (lowest to highest): inherited env, config env, user env file,
+	/// runner apps' env, the app's env, the command's env, `--env` from the command line.
+	pub fn resolve(
 		&self,
 		cmd: &str,
 		query: &str,
 		args: Vec<String>,
 		env: Map<String, String>,
-		background: bool
-	) -> Result<()> {
+		opts: &ResolveOpts
+	) -> Result<ResolvedParts> {
+		let source = self.find_app(query)?;
+		self.resolve_source(cmd, source, args, env, opts)
+	}
+
+	/// like `resolve`, for an app that was already looked up (see find_app)
+	pub fn resolve_source(
+		&self,
+		cmd: &str,
+		source: &AppSource,
+		args: Vec<String>,
+		env: Map<String, String>,
+		opts: &ResolveOpts
+	) -> Result<ResolvedParts> {
 		let resolver = Resolver::new(self);
 
 		// 1. resolve @chain
-		let path = self.find_app(query)?;
-		let name = self.apps.iter().find(|(_, p)| *p == path).map(|(n, _)| n).ok_or(anyhow!("app definition not found for {query}"))?;
-		let app = self.load_app_from(path)?;
-		let parts = resolver.resolve_command(&app, cmd)?;
+		let name = self.name_of(source).ok_or(anyhow!("app definition not found for '{source}'"))?;
+		verbose(1, format_args!("app '{name}' is defined in {source}"));
+		let app = self.load_app_from(source)?;
+		let parts = resolver.resolve_command(source, &app, cmd)?;
+		if !parts.chain.is_empty() {
+			verbose(1, format_args!("runner chain: {name} -> @{}", parts.chain.join(" -> @")));
+		}
 
-		// 2. sandwich args (%! replacement)
-		let intermediate_args = sandwich_args(parts.args, args);
+		// 2. sandwich args (%! replacement), after putting the config's default_args first
+		let mut resolved_args = self.config.default_args.clone().unwrap_or_default();
+		resolved_args.extend(parts.args);
+		verbose(1, format_args!("args before sandwiching: {}", shell_words::join(&resolved_args)));
+		if !args.is_empty() {
+			verbose(1, format_args!("command line args: {}", shell_words::join(&args)));
+		}
+		let intermediate_args = sandwich_args(resolved_args, args);
+		verbose(1, format_args!("args after sandwiching: {}", shell_words::join(&intermediate_args)));
 
-		// 3. layer envs
-		let mut final_env = env;
+		// 3. layer envs, on top of the given env unless the inherited one is cleared
+		let mut final_env = if opts.clean_env { Map::new() } else { env };
+		let user_env = self.load_user_env(opts.user_env.as_deref())?;
+		if log::enabled(1) {
+			let layers = [
+				("config [env]", self.config.env.iter().flatten().map(|(k, _)| k.as_str()).collect::<Vec<_>>()),
+				("user env file", user_env.keys().map(String::as_str).collect()),
+				("app and runner [env]", parts.env.keys().map(String::as_str).collect()),
+				("--env", opts.env.iter().map(|(k, _)| k.as_str()).collect()),
+			];
+			for (layer, keys) in layers.iter().filter(|(_, keys)| !keys.is_empty()) {
+				verbose(1, format_args!("env from {layer}: {}", keys.join(", ")));
+			}
+		}
 		if let Some(env) = &self.config.env {
 			final_env.extend(env.clone());
 		}
+		final_env.extend(user_env);
 		final_env.extend(parts.env);
+		final_env.extend(opts.env.iter().cloned());
 
 		// 4. resolve variable (only on what we are about to use)
-		let final_bin = resolver.expand(Some(&app), &parts.bin)?;
+		let mut final_bin = resolver.expand(Some(&app), &parts.bin)?;
 
-		let final_args: Vec<String> = intermediate_args
-			.into_iter()
-			.map(|arg| resolver.expand(Some(&app), &arg))
-			.collect::<Result<Vec<_>>>()?;
+		let mut final_args: Vec<String> = Vec::new();
+		for arg in intermediate_args {
+			let expanded = resolver.expand(Some(&app), &arg)?;
+			// only drop args that became empty (or whitespace) through expansion, literal ones like "" are kept on purpose
+			if self.config.drop_empty_args && expanded != arg && expanded.trim().is_empty() {
+				continue;
+			}
+			final_args.push(expanded);
+		}
+
+		// shell mode: the expanded bin and args are joined as they are, for the shell to interpret
+		if parts.shell == Some(true) {
+			let line = std::iter::once(final_bin).chain(final_args).collect::<Vec<_>>().join(" ");
+			if log::enabled(1) {
+				verbose(1, format_args!("running through the shell: {}", resolver.mask(&line)));
+			}
+			(final_bin, final_args) = if cfg!(windows) {
+				("cmd".to_string(), vec!["/C".to_string(), line])
+			} else {
+				("sh".to_string(), vec!["-c".to_string(), line])
+			};
+		}
 
 		let final_env: Map<String, String> = final_env
 			.into_iter()
@@ -165,33 +488,817 @@ impl Launcher {
 			})
 			.collect::<Result<Map<_, _>>>()?;
 
-		// 5. build and launch
+		let final_dir = match &opts.cwd {
+			Some(cwd) if !cwd.is_dir() => bail!("working directory '{}' does not exist", cwd.display()),
+			Some(cwd) => Some(cwd.to_string_lossy().into_owned()),
+			None => parts.working_dir
+				.map(|dir| resolver.expand(Some(&app), &dir))
+				.transpose()?,
+		};
+		let final_log = parts.log
+			.map(|log| resolver.expand(Some(&app), &log))
+			.transpose()?;
+
+		let mut chain = parts.chain;
+		chain.insert(0, name.clone());
+
+		// hooks are split before expanding, so a variable containing spaces stays one argument
+		let expand_hooks = |hooks: &Option<Vec<String>>, kind: &str| -> Result<Vec<Vec<String>>> {
+			hooks.iter().flatten()
+				.map(|line| {
+					shell_words::split(line)
+						.map_err(|e| anyhow!("failed to parse {kind} hook '{line}': {e}"))?
+						.iter()
+						.map(|word| resolver.expand(Some(&app), word))
+						.collect::<Result<Vec<_>>>()
+				})
+				.filter(|hook| !matches!(hook, Ok(words) if words.is_empty()))
+				.collect()
+		};
+		// global hooks wrap the app's own: before_launch, pre, (app), post, after_launch
+		let mut pre = expand_hooks(&self.config.before_launch, "before_launch")?;
+		pre.extend(expand_hooks(&app.pre, "pre")?);
+		let mut post = expand_hooks(&app.post, "post")?;
+		post.extend(expand_hooks(&self.config.after_launch, "after_launch")?);
+
+		Ok(ResolvedParts {
+			bin: final_bin,
+			args: final_args,
+			env: final_env,
+			working_dir: final_dir,
+			log: final_log,
+			detach_after: parts.detach_after,
+			shell: parts.shell,
+			chain,
+			pre,
+			post,
+			background: app.background,
+			retries: app.retries,
+			retry_delay_ms: app.retry_delay_ms,
+			secrets: resolver.take_secrets(),
+		})
+	}
+
+	/// resolves an app's 'launch' command the way `ran launch` would without any launch options,
+	/// given the cli args and the environment to start from. see `resolve`.
+	pub fn resolve_launch(&self, query: &str, args: Vec<String>, env: Map<String, String>) -> Result<ResolvedParts> {
+		self.resolve("launch", query, args, env, &ResolveOpts::default())
+	}
+
+	/// lints an app definition: every command's runner chain must resolve (no missing or circular
+	/// runners) and its executable must exist. referencing undefined variables is only a warning.
+	pub fn check_app(&self, query: &str) -> Result<AppCheck> {
+		let source = self.find_app(query)?;
+		let app = self.load_app_from(source)?;
+		let mut check = AppCheck::default();
+
+		if app.cmds.is_empty() {
+			check.errors.push("no commands are defined".into());
+		} else if !app.cmds.contains_key("launch") {
+			check.warnings.push("there's no 'launch' command, so 'ran launch' won't work".into());
+		}
+
+		for name in app.cmds.keys() {
+			let resolver = Resolver::new(self);
+			let parts = match resolver.resolve_command(source, &app, name) {
+				Ok(parts) => parts,
+				Err(e) => {
+					check.errors.push(format!("cmds.{name}: {e}"));
+					continue;
+				}
+			};
+
+			let bin = resolver.expand(Some(&app), &parts.bin)?;
+			for arg in parts.args.iter().chain(parts.env.values()) {
+				resolver.expand(Some(&app), arg)?;
+			}
+			let dir = parts.working_dir
+				.map(|dir| resolver.expand(Some(&app), &dir))
+				.transpose()?;
+			// the PATH the command would run with, app and runner env before the config's
+			let env_path = parts.env.get("PATH")
+				.or_else(|| self.config.env.as_ref()?.get("PATH"))
+				.map(|path| resolver.expand(Some(&app), path))
+				.transpose()?;
+
+			if bin.trim().is_empty() {
+				check.errors.push(format!("cmds.{name}: bin is empty"));
+			} else if find_bin(&bin, dir.as_deref(), env_path.as_deref()).is_none() {
+				check.errors.push(format!("cmds.{name}: '{bin}' was not found on PATH"));
+			}
+			if let Some(dir) = &dir && !Path::new(dir).is_dir() {
+				check.warnings.push(format!("cmds.{name}: working directory '{dir}' does not exist"));
+			}
+			for var in resolver.take_undefined() {
+				check.warnings.push(format!("cmds.{name}: variable '{var}' is not defined"));
+			}
+		}
+		Ok(check)
+	}
+
+	/// (private) appends a launch to the history. failing to do so never aborts a launch.
+	fn record_history(&self, entry: HistoryEntry) {
+		let max = self.config.history_size.unwrap_or(DEFAULT_HISTORY_SIZE);
+		if let Err(e) = entry.record(&self.config_path, max) {
+			eprintln!("failed to record launch history: {e}");
+		}
+	}
+
+	/// (private) counts a launch of 'name' (see LaunchCounter). failing to do so never aborts a launch.
+	fn count_launch(&self, name: &str, cmd: &str) {
+		// only launches count as playing, other commands (e.g. a config tool) don't
+		if cmd == "launch" && let Err(e) = LaunchCounter::record(&self.config_path, name) {
+			eprintln!("failed to count launch: {e}");
+		}
+	}
+
+	/// resolves an app's command (see Launcher::resolve) and runs it: pre hooks, the command itself
+	/// (with retries, timeout and history) and post hooks. for foreground launches this waits for the app,
+	/// failing if it exits with an error. `cmd` is the command to run, e.g. "launch"
+	pub fn launch_app(
+		&self,
+		cmd: &str,
+		query: &str,
+		args: Vec<String>,
+		env: Map<String, String>,
+		opts: &LaunchOpts
+	) -> Result<()> {
+		let parts = self.resolve(cmd, query, args, env, &opts.into())?;
+		self.launch_resolved(cmd, parts, opts)
+	}
+
+	/// launches several apps (their 'launch' command) one after another.
+	/// with 'atomic', every app is resolved first and nothing is launched unless all of them resolve,
+	/// otherwise a failing app stops the apps after it but the ones before it are already running.
+	pub fn launch_group(
+		&self,
+		queries: &[String],
+		env: Map<String, String>,
+		atomic: bool,
+		opts: &LaunchOpts
+	) -> Result<()> {
+		if !atomic {
+			for query in queries {
+				self.launch_app("launch", query, Vec::new(), env.clone(), opts)
+					.map_err(|e| anyhow!("failed to launch '{query}': {e}"))?;
+			}
+			return Ok(());
+		}
+
+		// pre-flight: resolve everything (and make sure every executable exists) before spawning anything
+		let mut resolved = Vec::new();
+		let mut failures = Vec::new();
+		for query in queries {
+			match self.resolve("launch", query, Vec::new(), env.clone(), &opts.into()) {
+				Ok(parts) if find_bin(
+					&parts.bin,
+					parts.working_dir.as_deref(),
+					parts.env.get("PATH").map(String::as_str),
+				).is_none() => {
+					failures.push(format!("{query}: binary '{}' not found on PATH", parts.bin));
+				}
+				Ok(parts) => resolved.push(parts),
+				Err(e) => failures.push(format!("{query}: {e}")),
+			}
+		}
+		if !failures.is_empty() {
+			bail!(
+				"not launching anything, {} app(s) failed to resolve:\n  {}",
+				failures.len(),
+				failures.join("\n  ")
+			);
+		}
+
+		for parts in resolved {
+			self.launch_resolved("launch", parts, opts)?;
+		}
+		Ok(())
+	}
+
+	/// (private) launches an already resolved command (see Launcher::resolve)
+	fn launch_resolved(&self, cmd: &str, parts: ResolvedParts, opts: &LaunchOpts) -> Result<()> {
+		let ResolvedParts {
+			bin: final_bin,
+			args: final_args,
+			env: final_env,
+			working_dir,
+			log,
+			detach_after,
+			// already applied, bin and args are the shell's
+			shell: _,
+			chain,
+			pre,
+			post,
+			background,
+			retries,
+			retry_delay_ms,
+			secrets,
+		} = parts;
+		// -b/-F if given, otherwise the app's default
+		let background = opts.background || (!opts.foreground && background.unwrap_or(false));
+		let name = &chain[0];
+		// once detached, the app is out of ran's reach and couldn't be killed when the time is up
+		if let (Some(secs), Some(_), false) = (detach_after, opts.timeout, background) {
+			bail!("--timeout can't be used with app '{name}', it detaches after {secs}s (detach_after)");
+		}
+
+		// keyring secrets are never printed (or written to the history)
+		let mask = |text: &str| mask_secrets(text, &secrets);
+
+		if opts.print_env {
+			if opts.clean_env {
+				eprintln!("{} (the inherited environment is cleared)", "-".red());
+			}
+			for (k, v) in &final_env {
+				match std::env::var(k).ok().filter(|_| !opts.clean_env) {
+					Some(old) if old == *v => {}
+					Some(old) => eprintln!("{} {k}: {} -> {}", "~".yellow(), mask(&old), mask(v)),
+					None => eprintln!("{} {k}={}", "+".green(), mask(v)),
+				}
+			}
+		}
+
+		if opts.dry_run {
+			println!("{} {}", "app:".bright_black(), chain.join(" -> "));
+			println!("{} {}", "bin:".bright_black(), mask(&final_bin));
+			println!("{} {}", "args:".bright_black(), mask(&shell_words::join(&final_args)));
+			if let Some(dir) = &working_dir {
+				println!("{} {}", "working dir:".bright_black(), mask(dir));
+			}
+			if let Some(log) = &log {
+				println!("{} {}", "log:".bright_black(), mask(log));
+			}
+			if background {
+				println!("{} true", "background:".bright_black());
+			}
+			if opts.clean_env {
+				println!("{} true", "clean env:".bright_black());
+			}
+			for hook in &pre {
+				println!("{} {}", "pre:".bright_black(), mask(&shell_words::join(hook)));
+			}
+			for hook in &post {
+				println!("{} {}", "post:".bright_black(), mask(&shell_words::join(hook)));
+			}
+			println!("{}", "env:".bright_black());
+			let mut env: Vec<_> = final_env.iter().collect();
+			env.sort();
+			for (k, v) in env {
+				println!("  {k}={}", mask(v));
+			}
+			return Ok(());
+		}
+
+		let history_bin = mask(&final_bin);
+		let history_args: Vec<String> = final_args.iter().map(|arg| mask(arg)).collect();
+		verbose(1, format_args!(
+			"final command: {}",
+			shell_words::join(std::iter::once(&history_bin).chain(&history_args))
+		));
+		let history = |background, timestamp, exit_code| HistoryEntry {
+			timestamp,
+			app: name.clone(),
+			cmd: cmd.to_string(),
+			bin: history_bin.clone(),
+			args: history_args.clone(),
+			background,
+			exit_code,
+		};
+
+		let final_dir = match working_dir {
+			Some(dir) => PathBuf::from(dir),
+			None => std::env::current_dir()?,
+		};
+
+		// fail early with a useful message instead of spawn's bare "no such file or directory"
+		if find_bin(
+			&final_bin,
+			Some(&final_dir.to_string_lossy()),
+			final_env.get("PATH").map(String::as_str),
+		).is_none() {
+			bail!("binary '{final_bin}' for app '{name}' not found on PATH");
+		}
+
+		for hook in &pre {
+			let status = run_hook(hook, &final_env, opts.clean_env, &final_dir)
+				.map_err(|e| anyhow!("failed to run pre hook '{}' for app '{name}': {e}", hook[0]))?;
+			if !status.success() {
+				bail!("pre hook '{}' for app '{name}' exited with {status}, not launching", hook[0]);
+			}
+		}
+
+		// build and launch
 		if background {
-			let mut proc = Command::new(final_bin);
-			proc.args(final_args)
+			let mut proc = match self.config.terminal_runner.as_deref().map(shell_words::split) {
+				Some(Ok(runner)) if !runner.is_empty() => {
+					if find_in_path(&runner[0]).is_none() {
+						bail!("terminal_runner '{}' not found on PATH", runner[0]);
+					}
+					// open in a new terminal: <terminal_runner...> <bin> <args...>
+					let mut proc = Command::new(&runner[0]);
+					proc.args(&runner[1..]).arg(&final_bin);
+					proc
+				}
+				Some(Err(e)) => bail!("failed to parse terminal_runner: {e}"),
+				_ => Command::new(&final_bin),
+			};
+			let (stdout, stderr) = match &log {
+				Some(log) => log_stdio(log, &final_dir)?,
+				None => (Stdio::null(), Stdio::null()),
+			};
+			if opts.clean_env {
+				proc.env_clear();
+			}
+			proc.args(&final_args)
+				.envs(&final_env)
 				.stdin(Stdio::null())
-				.stdout(Stdio::null())
-				.stderr(Stdio::null())
-				.current_dir(std::env::current_dir()?);
-			// spawn and immediately forget
-			let _ = proc.spawn();
-			match cmd {
-				"launch" => println!("launched app '{name}' in the background!"),
-				_ => println!("started executing command '{cmd}' for app '{name}' in the background!"),
+				.stdout(stdout)
+				.stderr(stderr)
+				.current_dir(&final_dir);
+			detach(&mut proc);
+			// spawn and immediately forget (but do report if it couldn't start at all)
+			proc.spawn()
+				.map_err(|e| anyhow!("failed to start '{final_bin}' for app '{name}': {e}"))?;
+			self.count_launch(name, cmd);
+			self.record_history(history(true, chrono::Local::now(), None));
+			if !opts.quiet {
+				match cmd {
+					"launch" => eprintln!("launched app '{name}' in the background!"),
+					_ => eprintln!("started executing command '{cmd}' for app '{name}' in the background!"),
+				}
 			}
 		} else {
-			let mut proc = Command::new(final_bin);
-			proc.args(final_args).envs(final_env).current_dir(std::env::current_dir()?);
+			let mut proc = Command::new(&final_bin);
+			if opts.clean_env {
+				proc.env_clear();
+			}
+			proc.args(&final_args).envs(&final_env).current_dir(&final_dir);
+			// an app ran detaches from gets its own session right away, so it survives ran's terminal
+			// being closed once ran has returned (like a background launch)
+			if detach_after.is_some() {
+				detach(&mut proc);
+			}
+			if opts.null_io {
+				proc.stdout(Stdio::null()).stderr(Stdio::null());
+			} else if let Some(log) = &log {
+				let (stdout, stderr) = log_stdio(log, &final_dir)?;
+				proc.stdout(stdout).stderr(stderr);
+			}
 			// wait for exit
-			match cmd {
-				"launch" => println!("launching app '{name}'..."),
-				_ => println!("running command '{cmd}' for app '{name}'..."),
+			if !opts.quiet {
+				match cmd {
+					"launch" => eprintln!("launching app '{name}'..."),
+					_ => eprintln!("running command '{cmd}' for app '{name}'..."),
+				}
 			}
-			let status = proc.status()?;
-			if !status.success() {
+			// with a timeout, the app gets its own process group (and the terminal) so whatever it
+			// starts is killed with it
+			let terminal = opts.timeout.map(|_| TerminalHandoff::prepare(&mut proc));
+
+			let retries = if opts.no_retry { 0 } else { retries.unwrap_or(0) };
+			let mut attempt = 0;
+			let mut timed_out = None;
+			// ctrl-c and friends go to the app, and ran waits for it to exit instead of dying first
+			let signals = SignalForwarder::install();
+			let started = chrono::Local::now();
+			let status = loop {
+				let mut child = proc.spawn()
+					.map_err(|e| anyhow!("failed to start '{final_bin}' for app '{name}': {e}"))?;
+				// retries are part of the same launch
+				if attempt == 0 {
+					self.count_launch(name, cmd);
+				}
+				signals.watch(&child, opts.timeout.is_some());
+				let deadline = opts.timeout.map(|secs| (secs, Instant::now() + Duration::from_secs(secs)));
+
+				// hybrid launch: stay attached for a while, then leave the process running on its own.
+				// post hooks are for after the app exited, so they don't run if ran detaches from it
+				if let Some(secs) = detach_after {
+					let deadline = Instant::now() + Duration::from_secs(secs);
+					while Instant::now() < deadline {
+						if child.try_wait()?.is_some() {
+							break;
+						}
+						thread::sleep(Duration::from_millis(100));
+					}
+					if child.try_wait()?.is_none() {
+						if !opts.quiet {
+							eprintln!("detached from app '{name}' after {secs}s, it keeps running in the background.");
+						}
+						self.record_history(history(false, started, None));
+						return Ok(());
+					}
+				}
+
+				let status = match deadline {
+					Some((secs, deadline)) => match wait_until(&mut child, deadline)? {
+						Some(status) => status,
+						None => {
+							kill_tree(&mut child);
+							timed_out = Some(secs);
+							break child.wait()?;
+						}
+					},
+					None => child.wait()?,
+				};
+				// no exit code means it was killed by a signal, which is taken as intended
+				if status.success() || status.code().is_none() || signals.received().is_some() || attempt >= retries {
+					break status;
+				}
+				attempt += 1;
+				eprintln!("process exited with {status}, retrying ({attempt}/{retries})...");
+				thread::sleep(Duration::from_millis(retry_delay_ms.unwrap_or(DEFAULT_RETRY_DELAY_MS)));
+			};
+			// recorded once the app exited, so the history has its exit code
+			self.record_history(history(false, started, status.code()));
+			let interrupted = signals.received();
+			drop(signals);
+			drop(terminal);
+			if !status.success() && timed_out.is_none() && interrupted.is_none() {
 				eprintln!("process exited with {}", status);
 			}
+
+			// post hooks run regardless of the exit status, and a failing one doesn't stop the others
+			for hook in &post {
+				match run_hook(hook, &final_env, opts.clean_env, &final_dir) {
+					Ok(status) if !status.success() => {
+						eprintln!("post hook '{}' exited with {status}", hook[0]);
+					}
+					Err(e) => eprintln!("failed to run post hook '{}': {e}", hook[0]),
+					_ => {}
+				}
+			}
+			if let Some(secs) = timed_out {
+				return Err(LauncherError::TimedOut { app: name.clone(), secs }.into());
+			}
+			if let Some(signal) = interrupted {
+				return Err(LauncherError::Interrupted { app: name.clone(), signal }.into());
+			}
 		}
 		Ok(())
 	}
 }
+
+// --- functions ---
+/// describes every group of apps sharing a leaf name (e.g. "doom" and "games/doom"), since looking
+/// that name up matches all of them and has to ask which one was meant (see `app list`, validate-all and doctor)
+pub fn ambiguous_names<'a>(names: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+	let mut by_leaf: Map<&str, Vec<&str>> = Map::new();
+	for name in names {
+		let leaf = name.split('/').next_back().unwrap_or(name);
+		by_leaf.entry(leaf).or_default().push(name);
+	}
+	by_leaf.iter()
+		.filter(|(_, names)| names.len() > 1)
+		.map(|(leaf, names)| format!("'{leaf}' matches several apps ({}), use the full name to pick one", names.join(", ")))
+		.collect()
+}
+
+/// (private) locates an executable like the launch would: paths are taken relative to the working
+/// directory (if any), bare names are looked up in the app's PATH ('env_path'), or ran's own $PATH
+/// if the app doesn't set one
+fn find_bin(bin: &str, working_dir: Option<&str>, env_path: Option<&str>) -> Option<PathBuf> {
+	let path = Path::new(bin);
+	match (working_dir, env_path) {
+		(Some(dir), _) if !path.is_absolute() && path.components().count() > 1 => {
+			find_in_path(&Path::new(dir).join(path).to_string_lossy())
+		}
+		(_, Some(env_path)) => find_in_search_path(bin, OsStr::new(env_path)),
+		_ => find_in_path(bin),
+	}
+}
+
+/// (private) runs a hook (program and args) in the foreground and waits for it to exit,
+/// with only 'env' if 'clean_env' is set
+fn run_hook(hook: &[String], env: &Map<String, String>, clean_env: bool, dir: &Path) -> std::io::Result<ExitStatus> {
+	let mut proc = Command::new(&hook[0]);
+	if clean_env {
+		proc.env_clear();
+	}
+	proc.args(&hook[1..])
+		.envs(env)
+		.current_dir(dir)
+		.status()
+}
+
+/// (private) makes a background launch independent of ran's terminal: on unix it starts a new
+/// session (so closing the terminal doesn't send it SIGHUP), on windows a new process group
+/// without ran's console (so ctrl-c and closing the console don't reach it)
+fn detach(proc: &mut Command) {
+	#[cfg(unix)]
+	{
+		use std::os::unix::process::CommandExt;
+		// SAFETY: setsid() is async-signal-safe, and nothing else runs between fork and exec
+		unsafe {
+			proc.pre_exec(|| {
+				libc::setsid();
+				Ok(())
+			});
+		}
+	}
+	#[cfg(windows)]
+	{
+		use std::os::windows::process::CommandExt;
+		const DETACHED_PROCESS: u32 = 0x0000_0008;
+		const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+		proc.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+	}
+}
+
+/// (private) waits for 'child' to exit until 'deadline', returning None if it's still running then
+fn wait_until(child: &mut Child, deadline: Instant) -> Result<Option<ExitStatus>> {
+	loop {
+		if let Some(status) = child.try_wait()? {
+			return Ok(Some(status));
+		}
+		if Instant::now() >= deadline {
+			return Ok(None);
+		}
+		thread::sleep(Duration::from_millis(100));
+	}
+}
+
+/// (private) kills 'child', along with its process group on unix (see `--timeout`)
+fn kill_tree(child: &mut Child) {
+	#[cfg(unix)]
+	// SAFETY: kill() has no memory-safety requirements, a negative pid addresses the process group
+	unsafe {
+		libc::kill(-(child.id() as i32), libc::SIGKILL);
+	}
+	let _ = child.kill();
+}
+
+/// replaces every occurrence of a secret in 'text' with asterisks
+pub fn mask_secrets(text: &str, secrets: &[String]) -> String {
+	secrets.iter().fold(text.to_string(), |text, secret| text.replace(secret.as_str(), "********"))
+}
+
+/// (private) opens the log file for a launch (appending, relative paths are relative to 'dir'),
+/// returning it as both stdout and stderr. "null" discards the output instead.
+fn log_stdio(log: &str, dir: &Path) -> Result<(Stdio, Stdio)> {
+	if log.trim() == "null" {
+		return Ok((Stdio::null(), Stdio::null()));
+	}
+	let path = dir.join(log);
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	let file = fs::OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(&path)
+		.map_err(|e| anyhow!("failed to open log file '{}': {e}", path.display()))?;
+	Ok((Stdio::from(file.try_clone()?), Stdio::from(file)))
+}
+
+// --- tests ---
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	/// a config dir with the given app files (name -> toml) and a launcher for it
+	pub(crate) fn setup(config: Config, apps: &[(&str, &str)]) -> (TempDir, Launcher) {
+		let dir = TempDir::new().unwrap();
+		for (name, toml) in apps {
+			let file = dir.path().join("apps").join(format!("{name}.toml"));
+			fs::create_dir_all(file.parent().unwrap()).unwrap();
+			fs::write(file, toml).unwrap();
+		}
+		let launcher = Launcher::init(dir.path(), config).unwrap();
+		(dir, launcher)
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn finds_bin_on_env_extended_path() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let tools = TempDir::new().unwrap();
+		let tool = tools.path().join("ran-test-tool");
+		fs::write(&tool, "#!/bin/sh\n").unwrap();
+		fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+
+		let app = format!(
+			"[env]\nPATH = \"${{env.PATH}}:{}\"\n\n[cmds.launch]\nbin = \"ran-test-tool\"\n",
+			tools.path().display()
+		);
+		let (_dir, l) = setup(Config::default(), &[("tool", &app)]);
+
+		let parts = l.resolve_launch("tool", Vec::new(), std::env::vars().collect()).unwrap();
+		assert!(find_in_path("ran-test-tool").is_none());
+		assert_eq!(find_bin(&parts.bin, None, parts.env.get("PATH").map(String::as_str)), Some(tool));
+
+		let check = l.check_app("tool").unwrap();
+		assert!(check.errors.is_empty(), "{:?}", check.errors);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn passes_args_to_the_app_verbatim() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let scripts = TempDir::new().unwrap();
+		let script = scripts.path().join("echo-argv");
+		let out = scripts.path().join("argv.txt");
+		fs::write(&script, format!("#!/bin/sh\nprintf '%s\\n' \"$@\" > '{}'\n", out.display())).unwrap();
+		fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+		let app = format!("[cmds.launch]\nbin = \"{}\"\nargs = [\"%!\"]\n", script.display());
+		let (_dir, l) = setup(Config::default(), &[("argv", &app)]);
+		let args = ["--fullscreen", "-q", "--", "two words", ""].map(String::from).to_vec();
+		let opts = LaunchOpts { quiet: true, ..LaunchOpts::default() };
+		l.launch_app("launch", "argv", args, std::env::vars().collect(), &opts).unwrap();
+
+		assert_eq!(fs::read_to_string(out).unwrap(), "--fullscreen\n-q\n--\ntwo words\n\n");
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn detached_launches_get_their_own_session() {
+		let mut proc = Command::new("sleep");
+		proc.arg("5");
+		detach(&mut proc);
+		let mut child = proc.spawn().unwrap();
+		let pid = child.id() as libc::pid_t;
+
+		// SAFETY: these only query the process group and session of our own child
+		let (group, session) = unsafe { (libc::getpgid(pid), libc::getsid(pid)) };
+		let _ = child.kill();
+		let _ = child.wait();
+		assert_eq!(group, pid);
+		assert_eq!(session, pid);
+		// SAFETY: as above, for ran's own process
+		assert_ne!(group, unsafe { libc::getpgrp() });
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn records_only_launches_that_started() {
+		let (dir, l) = setup(Config::default(), &[
+			("works", "[cmds.launch]\nbin = \"true\"\n"),
+			("broken", "[cmds.launch]\nbin = \"true\"\nworking_dir = \"/ran-test-missing-dir\"\n"),
+		]);
+		let opts = LaunchOpts { quiet: true, ..Default::default() };
+
+		assert!(l.launch_app("launch", "broken", Vec::new(), Map::new(), &opts).is_err());
+		assert!(HistoryEntry::load_all(dir.path()).unwrap().is_empty());
+		assert!(LaunchCounter::load_all(dir.path()).is_empty());
+
+		l.launch_app("launch", "works", Vec::new(), Map::new(), &opts).unwrap();
+		let history = HistoryEntry::load_all(dir.path()).unwrap();
+		assert_eq!(history.iter().map(|entry| entry.app.as_str()).collect::<Vec<_>>(), ["works"]);
+		assert_eq!(history[0].exit_code, Some(0));
+		assert_eq!(LaunchCounter::load_all(dir.path())["works"].launch_count, 1);
+	}
+
+	#[test]
+	fn detach_after_cant_be_timed_out() {
+		let (dir, l) = setup(Config::default(), &[("hybrid", "[cmds.launch]\nbin = \"true\"\ndetach_after = 5\n")]);
+		let opts = LaunchOpts { quiet: true, timeout: Some(10), ..Default::default() };
+
+		let error = l.launch_app("launch", "hybrid", Vec::new(), Map::new(), &opts).unwrap_err();
+		assert!(error.to_string().contains("--timeout can't be used"), "{error}");
+		// rejected before anything was started
+		assert!(HistoryEntry::load_all(dir.path()).unwrap().is_empty());
+	}
+
+	#[test]
+	fn finds_inline_apps_by_name() {
+		let config = Config {
+			apps: Some(Map::from([
+				("htop".to_string(), toml::from_str("[cmds.launch]\nbin = \"htop\"\n").unwrap()),
+				("doom".to_string(), toml::from_str("[cmds.launch]\nbin = \"inline-doom\"\n").unwrap()),
+			])),
+			..Config::default()
+		};
+		let (dir, l) = setup(config, &[("doom", "[cmds.launch]\nbin = \"file-doom\"\n")]);
+
+		let htop = l.find_app("htop").unwrap();
+		assert!(matches!(htop, AppSource::Inline(name) if name == "htop"));
+		assert_eq!(l.load_app_from(htop).unwrap().cmds["launch"].bin, "htop");
+		assert!(l.app_file("htop").is_err());
+
+		// the file wins over the inline app of the same name
+		let doom = l.find_app("doom").unwrap();
+		assert_eq!(doom.file(), Some(dir.path().join("apps/doom.toml").as_path()));
+		assert_eq!(l.load_app_from(doom).unwrap().cmds["launch"].bin, "file-doom");
+	}
+
+	#[test]
+	fn finds_apps_sharing_a_name() {
+		let names: Vec<String> = ["doom", "games/doom", "games/quake", "old/games/doom", "tools/quake2"]
+			.map(String::from)
+			.into();
+		assert_eq!(
+			ambiguous_names(&names),
+			["'doom' matches several apps (doom, games/doom, old/games/doom), use the full name to pick one"]
+		);
+		assert!(ambiguous_names(&names[2..]).is_empty());
+	}
+
+	#[test]
+	fn check_app_reports_problems() {
+		let (_dir, l) = setup(Config::default(), &[
+			("fine", "[cmds.launch]\nbin = \"sh\"\n"),
+			("broken", concat!(
+				"[cmds.setup]\nbin = \"ran-test-missing-bin\"\nargs = [\"$undefined_var\"]\n",
+				"working_dir = \"/ran-test-missing-dir\"\n\n",
+				"[cmds.play]\nbin = \"@ran-test-missing-runner\"\n",
+			)),
+		]);
+
+		let check = l.check_app("fine").unwrap();
+		assert!(check.errors.is_empty() && check.warnings.is_empty());
+
+		let check = l.check_app("broken").unwrap();
+		assert_eq!(check.errors, [
+			"cmds.play: app definition not found for ran-test-missing-runner",
+			"cmds.setup: 'ran-test-missing-bin' was not found on PATH",
+		]);
+		assert_eq!(check.warnings, [
+			"there's no 'launch' command, so 'ran launch' won't work",
+			"cmds.setup: working directory '/ran-test-missing-dir' does not exist",
+			"cmds.setup: variable 'undefined_var' is not defined",
+		]);
+	}
+
+	#[test]
+	fn sandwiches_args_through_runner() {
+		let (_dir, l) = setup(Config::default(), &[
+			("runner", "[cmds.launch]\nbin = \"wine\"\nargs = [\"--runner\", \"%!\", \"--end\"]\n"),
+			("game", "[cmds.launch]\nbin = \"@runner\"\nargs = [\"game.exe\", \"%!\"]\n"),
+		]);
+
+		let parts = l.resolve_launch("game", vec!["--cli".into()], Map::new()).unwrap();
+		assert_eq!(parts.bin, "wine");
+		assert_eq!(parts.args, ["--runner", "game.exe", "--cli", "--end"]);
+		assert_eq!(parts.chain, ["game", "runner"]);
+	}
+
+	#[test]
+	fn layers_env_in_order() {
+		let config = Config {
+			env: Some(Map::from([("A".into(), "config".into()), ("B".into(), "config".into())])),
+			..Config::default()
+		};
+		let (dir, l) = setup(config, &[
+			("game", "[env]\nC = \"app\"\nD = \"app\"\n\n[cmds.launch]\nbin = \"game\"\n"),
+		]);
+		fs::write(dir.path().join("launch.env"), "B=file\nC=file\n").unwrap();
+		let opts = ResolveOpts { env: vec![("D".into(), "cli".into())], ..ResolveOpts::default() };
+		let inherited = Map::from([("A".into(), "inherited".into()), ("E".into(), "inherited".into())]);
+
+		let env = l.resolve("launch", "game", Vec::new(), inherited, &opts).unwrap().env;
+		assert_eq!(env["A"], "config");
+		assert_eq!(env["B"], "file");
+		assert_eq!(env["C"], "app");
+		assert_eq!(env["D"], "cli");
+		assert_eq!(env["E"], "inherited");
+	}
+
+	#[test]
+	fn extends_inherited_path() {
+		let path = std::env::var("PATH").unwrap();
+		let config = Config {
+			env: Some(Map::from([("PATH".into(), "${env.PATH}:/extra".into())])),
+			..Config::default()
+		};
+		let (_dir, l) = setup(config, &[
+			("append", "[cmds.launch]\nbin = \"game\"\n"),
+			("prepend", "[env]\nPATH = \"/first:${env.PATH}\"\n\n[cmds.launch]\nbin = \"game\"\n"),
+		]);
+		let inherited: Map<String, String> = std::env::vars().collect();
+
+		let env = l.resolve_launch("append", Vec::new(), inherited.clone()).unwrap().env;
+		assert_eq!(env["PATH"], format!("{path}:/extra"));
+
+		// each layer replaces the variable as a whole, and ${env.PATH} is always ran's own PATH
+		let env = l.resolve_launch("prepend", Vec::new(), inherited).unwrap().env;
+		assert_eq!(env["PATH"], format!("/first:{path}"));
+	}
+
+	#[test]
+	fn drops_args_that_expand_to_nothing() {
+		let app = "[vars]\nempty = \"\"\n\n[cmds.launch]\nbin = \"game\"\nargs = [\"$empty\", \"\", \"-x\"]\n";
+
+		let (_dir, l) = setup(Config::default(), &[("game", app)]);
+		let parts = l.resolve_launch("game", Vec::new(), Map::new()).unwrap();
+		assert_eq!(parts.args, ["", "", "-x"]);
+
+		let (_dir, l) = setup(Config { drop_empty_args: true, ..Config::default() }, &[("game", app)]);
+		let parts = l.resolve_launch("game", Vec::new(), Map::new()).unwrap();
+		// a literal "" is kept on purpose
+		assert_eq!(parts.args, ["", "-x"]);
+	}
+
+	#[test]
+	fn drops_whitespace_only_args_but_keeps_literal_ones() {
+		let app = "[vars]\nempty = \"\"\nblank = \"  \"\n\n[cmds.launch]\nbin = \"game\"\nargs = [\"$blank\", \" $empty \", \"  \", \"%!\"]\n";
+		let (_dir, l) = setup(Config { drop_empty_args: true, ..Config::default() }, &[("game", app)]);
+
+		// empty cli args are literal too
+		let parts = l.resolve_launch("game", vec!["".into(), "-x".into()], Map::new()).unwrap();
+		assert_eq!(parts.args, ["  ", "", "-x"]);
+	}
+}
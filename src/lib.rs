@@ -0,0 +1,45 @@
+// ran - run anything now
+// a simple but customizable command-line launcher for games and programs.
+
+// Copyright 2026 Hasibix Hasi
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ran as a library: finding, resolving and launching apps the same way the `ran` binary does.
+//!
+//! ```no_run
+//! use ran_launcher::{Config, Launcher, ResolveOpts};
+//! use std::path::Path;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let config_path = Path::new("/home/me/.config/ran");
+//! let launcher = Launcher::init(config_path, Config::load(&config_path.join("config.toml"))?)?;
+//! let app = launcher.load_app("games/doom")?;
+//! let parts = launcher.resolve("launch", "games/doom", Vec::new(), std::env::vars().collect(), &ResolveOpts::default())?;
+//! # Ok(())
+//! # }
+//! ```
+
+// --- modules ---
+pub mod app;
+pub mod config;
+pub mod history;
+pub mod launcher;
+pub mod resolver;
+pub mod util;
+
+// --- re-exports ---
+pub use crate::app::{App, Cmd, Inject, Meta};
+pub use crate::config::Config;
+pub use crate::launcher::{AppCheck, AppSource, LaunchOpts, Launcher, LauncherError, ResolveOpts};
+pub use crate::resolver::{ResolvedParts, Resolver};
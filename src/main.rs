@@ -16,37 +16,51 @@
 // limitations under the License.
 
 // --- modules ---
-mod util;
-mod app;
+mod bundle;
 mod cli;
-mod config;
+mod doctor;
 mod handler;
-mod launcher;
-mod resolver;
+mod stats;
 
 // --- imports ---
 use anyhow::{anyhow, Result};
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
+use ran_launcher::util::fs::default_config_path;
+use ran_launcher::LauncherError;
 use crate::cli::*;
 use crate::handler::CommandHandler;
-use crate::util::fs::default_config_path;
 
 // --- functions ---
 fn main() {
+	// shell completion scripts call back into ran for the current app names (see `ran completions`)
+	CompleteEnv::with_factory(Cli::command).var(COMPLETE_VAR).complete();
 	if let Some(e) = real_main().err() {
 		eprintln!("{}", e);
+		let code = match e.downcast_ref::<LauncherError>() {
+			// like timeout(1)
+			Some(LauncherError::TimedOut { .. }) => 124,
+			// like a shell reports a process killed by a signal
+			Some(LauncherError::Interrupted { signal, .. }) => 128 + signal,
+			Some(LauncherError::CircularRunner(_)) | None => 1,
+		};
+		std::process::exit(code);
 	}
 }
 
 fn real_main() -> Result<()> {
 	let cli = Cli::parse();
+	let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+	if cli.no_color || no_color_env || !atty::is(atty::Stream::Stdout) {
+		colored::control::set_override(false);
+	}
+	ran_launcher::util::log::set_verbosity(cli.verbose);
 	let config_path = if let Some(c) = cli.config {
-		PathBuf::from(c)
+		c
 	} else {
 		default_config_path("ran")?
 	};
-	let handler = CommandHandler::new(config_path);
+	let handler = CommandHandler::new(config_path, cli.config_file, cli.json);
 
 	let cmd = cli.cmd.ok_or(anyhow!("no command was supplied"))?;
 	handler.handle_command(cmd)
@@ -1,35 +1,113 @@
+// --- constants ---
+/// how many runner apps (`bin = "@name"`) a command may go through
+pub const MAX_RUNNER_DEPTH: usize = 32;
+
 // --- imports ---
 use anyhow::{anyhow, bail, Result};
 use indexmap::IndexMap as Map;
-use crate::app::App;
-use crate::launcher::Launcher;
+use chrono::Local;
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use crate::app::{App, Inject};
+use crate::launcher::{mask_secrets, AppSource, Launcher, LauncherError};
 use crate::util::args::sandwich_args;
+use crate::util::log::verbose;
 
 // --- definitions ---
 pub struct ResolvedParts {
 	pub bin: String,
 	pub args: Vec<String>,
 	pub env: Map<String, String>,
+	pub working_dir: Option<String>,
+	/// file stdout and stderr go to ("null" discards them), like working_dir the closest one wins
+	pub log: Option<String>,
+	/// seconds to stay attached to a foreground launch before detaching from it (skipping the post hooks)
+	pub detach_after: Option<u64>,
+	/// whether the command line is run through the system shell, like detach_after the closest one wins
+	pub shell: Option<bool>,
+	/// runner apps (`@name`) the command was resolved through, outermost first
+	pub chain: Vec<String>,
+	/// hooks to run around the launch (split into program and args), the config's `before_launch`
+	/// and `after_launch` included
+	pub pre: Vec<Vec<String>>,
+	pub post: Vec<Vec<String>>,
+	/// the launched app's `background` default (the cli flags win over it)
+	pub background: Option<bool>,
+	/// the launched app's `retries` and `retry_delay_ms`
+	pub retries: Option<u32>,
+	pub retry_delay_ms: Option<u64>,
+	/// secrets from the os keyring that ended up in the result, never to be printed
+	pub secrets: Vec<String>,
 }
 
+/// expands an app's variables, runners and aliases against a Launcher's config and apps
 pub struct Resolver<'a> {
 	pub launcher: &'a Launcher,
+	/// variables that were referenced but couldn't be resolved (and were left as is)
+	undefined: RefCell<Vec<String>>,
+	/// values read from the os keyring so far (so they can be masked when printing)
+	secrets: RefCell<Vec<String>>,
 }
 
 // --- implementations ---
 impl<'a> Resolver<'a> {
 	pub fn new(launcher: &'a Launcher) -> Self {
-		Self { launcher }
+		Self { launcher, undefined: RefCell::new(Vec::new()), secrets: RefCell::new(Vec::new()) }
+	}
+
+	/// returns (and forgets) all secrets that were read from the os keyring so far
+	pub fn take_secrets(&self) -> Vec<String> {
+		self.secrets.take()
+	}
+
+	/// returns 'text' with every secret read from the os keyring so far masked, for printing
+	pub fn mask(&self, text: &str) -> String {
+		mask_secrets(text, &self.secrets.borrow())
+	}
+
+	/// returns (and forgets) the names of all variables that couldn't be resolved so far
+	pub fn take_undefined(&self) -> Vec<String> {
+		self.undefined.take()
+	}
+
+	/// (private) remembers an unresolved variable for take_undefined
+	fn note_undefined(&self, name: &str) {
+		let mut undefined = self.undefined.borrow_mut();
+		if !undefined.iter().any(|n| n == name) {
+			undefined.push(name.to_string());
+		}
 	}
 
-	/// resolves the commands executable, arguments, and environment variables
+	/// resolves the commands executable, arguments, environment variables and working directory
 	/// supports nested runners (bin starting with '@')
-	pub fn resolve_command(&self, app: &App, command: &str) -> Result<ResolvedParts> {
-		let mut stack = Vec::new();
-		self.resolve_command_inner(&mut stack, app, command)
+	///
+	/// working directory precedence (highest first): the command's `working_dir`, the app's
+	/// `working_dir`, then whatever the runner chain resolved to. so a runner's directory is
+	/// only used when the app being launched doesn't specify one itself.
+	///
+	/// 'source' is where 'app' is defined, so a chain leading back to it is caught as a cycle.
+	pub fn resolve_command(&self, source: &AppSource, app: &App, command: &str) -> Result<ResolvedParts> {
+		let root = self.launcher.name_of(source).cloned().unwrap_or_else(|| source.to_string());
+		let mut stack = vec![root];
+		let mut visited = vec![(source.clone(), command.to_string())];
+		let mut parts = self.resolve_command_inner(&mut stack, &mut visited, app, command)?;
+		// the chain is just the runners, without the app itself
+		stack.remove(0);
+		parts.chain = stack;
+		Ok(parts)
 	}
 
-	fn resolve_command_inner(&self, stack: &mut Vec<String>, app: &App, command: &str) -> Result<ResolvedParts> {
+	/// (private) 'stack' holds the app and runner names for messages, 'visited' the (definition, command)
+	/// pairs already resolved. cycles are detected by the latter, so an app can use another one of its
+	/// own commands as a runner, and a runner referenced by two names (e.g. an alias) is still caught.
+	fn resolve_command_inner(
+		&self,
+		stack: &mut Vec<String>,
+		visited: &mut Vec<(AppSource, String)>,
+		app: &App,
+		command: &str
+	) -> Result<ResolvedParts> {
 		let cmd = app.cmds.get(command)
 			.ok_or(anyhow!("command not found: '{command}'"))?;
 
@@ -45,29 +123,56 @@ impl<'a> Resolver<'a> {
 				_ => bail!("invalid runner '{}'", cmd.bin),
 			};
 
-			if stack.iter().any(|s| s == runner_name) {
-				stack.push(runner_name.to_string());
-				bail!(
-					"infinite recursion on runner app resolution. stack: {}",
-					stack.join(" -> ")
-				)
-			}
+			let runner_source = self.launcher.find_app(runner_name)?.clone();
+			let key = (runner_source, sub_command.to_string());
 			stack.push(runner_name.to_string());
+			if visited.contains(&key) {
+				return Err(LauncherError::CircularRunner(stack.clone()).into());
+			}
+			// the first entry is the app itself, not a runner
+			if stack.len() - 1 > MAX_RUNNER_DEPTH {
+				bail!("runner chain is deeper than {MAX_RUNNER_DEPTH} apps: {}", stack.join(" -> "))
+			}
 
-			let runner_app = self.launcher.load_app(runner_name)?;
-			self.resolve_command_inner(stack, &runner_app, sub_command)?
+			let runner_app = self.launcher.load_app_from(&key.0)?;
+			visited.push(key);
+			self.resolve_command_inner(stack, visited, &runner_app, sub_command)?
 		} else {
 			// base case
 			ResolvedParts {
 				bin: cmd.bin.clone(),
 				args: Vec::new(),
 				env: Map::new(),
+				working_dir: None,
+				log: None,
+				detach_after: None,
+				shell: None,
+				chain: Vec::new(),
+				pre: Vec::new(),
+				post: Vec::new(),
+				background: None,
+				retries: None,
+				retry_delay_ms: None,
+				secrets: Vec::new(),
 			}
 		};
 
-		// merge arguments
+		// merge arguments (with the runner's, according to the command's inject mode)
 		res_parts.args = if !res_parts.args.is_empty() {
-			sandwich_args(res_parts.args, cmd.args.clone())
+			let mut runner_args = res_parts.args;
+			match cmd.inject.unwrap_or_default() {
+				Inject::Auto => sandwich_args(runner_args, cmd.args.clone()),
+				Inject::Prepend => {
+					runner_args.retain(|arg| arg != "%!");
+					cmd.args.iter().cloned().chain(runner_args).collect()
+				}
+				Inject::Append => {
+					runner_args.retain(|arg| arg != "%!");
+					runner_args.extend(cmd.args.iter().cloned());
+					runner_args
+				}
+				Inject::Replace => cmd.args.clone(),
+			}
 		} else {
 			cmd.args.clone()
 		};
@@ -80,9 +185,78 @@ impl<'a> Resolver<'a> {
 			res_parts.env.extend(e.clone());
 		}
 
+		// merge working directory (overrides the runner's, if any)
+		if let Some(dir) = cmd.working_dir.as_ref().or(app.working_dir.as_ref()) {
+			res_parts.working_dir = Some(dir.clone());
+		}
+		if let Some(log) = cmd.log.as_ref().or(app.log.as_ref()) {
+			res_parts.log = Some(log.clone());
+		}
+
+		// detach_after and shell work the same way (the command's own setting wins over its runner's)
+		if cmd.detach_after.is_some() {
+			res_parts.detach_after = cmd.detach_after;
+		}
+		if cmd.shell.is_some() {
+			res_parts.shell = cmd.shell;
+		}
+
 		Ok(res_parts)
 	}
 
+	/// returns a copy of 'app' where every command's runner chain (bin starting with '@') is resolved,
+	/// i.e. each command shows the executable, args, env and working directory it ends up with
+	pub fn resolve_runners(&self, source: &AppSource, app: &App) -> Result<App> {
+		let mut resolved = app.clone();
+		for (name, cmd) in resolved.cmds.iter_mut() {
+			let parts = self.resolve_command(source, app, name)?;
+			cmd.bin = parts.bin;
+			cmd.args = parts.args;
+			cmd.env = (!parts.env.is_empty()).then_some(parts.env);
+			cmd.working_dir = parts.working_dir;
+			cmd.log = parts.log;
+			cmd.detach_after = parts.detach_after;
+			cmd.shell = parts.shell;
+		}
+		Ok(resolved)
+	}
+
+	/// returns a copy of 'target' with variables expanded in every var, env value, working directory
+	/// and command, using 'app' as the context for `$NAME` and `${self.*}` lookups.
+	/// the copy is meant for showing, so keyring secrets are masked in it
+	pub fn expand_app(&self, app: &App, target: &App) -> Result<App> {
+		let expand = |value: &str| self.expand(Some(app), value).map(|expanded| self.mask(&expanded));
+		let expand_map = |map: &Option<Map<String, String>>| {
+			map.as_ref()
+				.map(|m| {
+					m.iter()
+						.map(|(k, v)| Ok((k.clone(), expand(v)?)))
+						.collect::<Result<Map<_, _>>>()
+				})
+				.transpose()
+		};
+		let expand_dir = |dir: &Option<String>| {
+			dir.as_ref().map(|d| expand(d)).transpose()
+		};
+
+		let mut expanded = target.clone();
+		expanded.vars = expand_map(&target.vars)?;
+		expanded.env = expand_map(&target.env)?;
+		expanded.working_dir = expand_dir(&target.working_dir)?;
+		expanded.log = expand_dir(&target.log)?;
+		for cmd in expanded.cmds.values_mut() {
+			cmd.bin = expand(&cmd.bin)?;
+			cmd.args = cmd.args
+				.iter()
+				.map(|arg| expand(arg))
+				.collect::<Result<Vec<_>>>()?;
+			cmd.env = expand_map(&cmd.env)?;
+			cmd.working_dir = expand_dir(&cmd.working_dir)?;
+			cmd.log = expand_dir(&cmd.log)?;
+		}
+		Ok(expanded)
+	}
+
 	/// recursively resolves a variable by key with infinite-loop detection
 	pub fn resolve_variable(&self, app: Option<&App>, key: &str, stack: &mut Vec<String>) -> Result<Option<String>> {
 		if stack.contains(&key.to_string()) {
@@ -96,30 +270,77 @@ impl<'a> Resolver<'a> {
 		stack.push(key.to_string());
 		let parts: Vec<&str> = key.split('.').collect();
 
+		// secrets are used as they are, a '$' in a password isn't a variable
+		#[cfg(feature = "keyring")]
+		if let ["keyring", service, account @ ..] = parts.as_slice() && !account.is_empty() {
+			stack.pop();
+			return Ok(self.read_secret(service, &account.join(".")));
+		}
+		// so is the environment ran runs in (unset variables are left as they are)
+		if let ["env", name] = parts.as_slice() {
+			stack.pop();
+			return Ok(std::env::var(name).ok());
+		}
+		let mut other_app = None;
+
 		let value = match parts.as_slice() {
 			["config", rest @ ..] => self.launcher.config.get_slice(rest),
 			["apps", app_name, rest @ ..] => {
-				self.launcher.load_app(*app_name).ok()
-					.and_then(|a| a.get_slice(rest))
+				other_app = self.launcher.load_app(app_name).ok();
+				other_app.as_ref().and_then(|a| a.get_slice(rest))
 			}
 			["self", rest @ ..] => app.and_then(|a| a.get_slice(rest)),
 			[k] => {
 				let resolved = app.and_then(|a| {
 					a.vars.as_ref().and_then(|vars| vars.get(*k).cloned())
 				});
-				resolved.or_else(|| self.launcher.config.vars.as_ref().and_then(|vars| vars.get(*k).cloned()))
+				let resolved = resolved.or_else(|| self.launcher.config.vars.as_ref().and_then(|vars| vars.get(*k).cloned()));
+				// built-in values are computed at launch time, user variables of the same name win
+				if resolved.is_none() && let Some(value) = dynamic_variable(k) {
+					stack.pop();
+					return Ok(Some(value));
+				}
+				if resolved.is_none() && let Ok(value) = std::env::var(k) {
+					// ran's own variables win over the environment, whose values are used as they are
+					stack.pop();
+					return Ok(Some(value));
+				}
+				resolved
 			}
 			_ => None,
 		};
 
-		let expanded = if let Some(val) = value {
-			self.expand_string(app, &val, stack)?
-		} else { None };
+		let expanded = match (value, &other_app) {
+			// another app's values are expanded in that app's own context. only the cross-app
+			// references carry over to its stack, so both apps can use the same variable names
+			// while cycles between apps are still detected.
+			(Some(val), Some(other)) => {
+				let mut scope: Vec<String> = stack.iter()
+					.filter(|k| k.starts_with("apps."))
+					.cloned()
+					.collect();
+				self.expand_string(Some(other), &val, &mut scope)?
+			}
+			(Some(val), None) => self.expand_string(app, &val, stack)?,
+			(None, _) => None,
+		};
 
 		stack.pop();
 		Ok(expanded)
 	}
 
+	/// (private) reads a secret from the os keyring, or None if there's no such entry (or no keyring)
+	#[cfg(feature = "keyring")]
+	fn read_secret(&self, service: &str, account: &str) -> Option<String> {
+		let secret = keyring::Entry::new(service, account)
+			.and_then(|entry| entry.get_password())
+			.ok()?;
+		if !secret.is_empty() {
+			self.secrets.borrow_mut().push(secret.clone());
+		}
+		Some(secret)
+	}
+
 	/// expands variables in a string with nested `${...}` and single-word `$NAME`
 	pub fn expand_string(&self, app: Option<&App>, text: &str, stack: &mut Vec<String>) -> Result<Option<String>> {
 		let mut result = String::with_capacity(text.len());
@@ -136,7 +357,7 @@ impl<'a> Resolver<'a> {
 					chars.next(); // skip '{'
 					let mut inner = String::new();
 					let mut brace_level = 1;
-					while let Some(ch) = chars.next() {
+					for ch in chars.by_ref() {
 						match ch {
 							'{' => {
 								inner.push(ch);
@@ -150,7 +371,16 @@ impl<'a> Resolver<'a> {
 							_ => inner.push(ch),
 						}
 					}
-					let val = self.resolve_variable(app, &inner, stack)?.unwrap_or(format!("${{{}}}", inner));
+					let val = match self.resolve_variable(app, &inner, stack)? {
+						Some(val) => {
+							log_expansion(&inner, &val);
+							val
+						}
+						None => {
+							self.note_undefined(&inner);
+							format!("${{{}}}", inner)
+						}
+					};
 					result.push_str(&val);
 				} else if let Some(&next_c) = chars.peek() {
 					// single-word $NAME
@@ -162,7 +392,16 @@ impl<'a> Resolver<'a> {
 								chars.next();
 							} else { break; }
 						}
-						let val = self.resolve_variable(app, &name, stack)?.unwrap_or(format!("${}", name));
+						let val = match self.resolve_variable(app, &name, stack)? {
+							Some(val) => {
+								log_expansion(&name, &val);
+								val
+							}
+							None => {
+								self.note_undefined(&name);
+								format!("${}", name)
+							}
+						};
 						result.push_str(&val);
 					} else {
 						result.push('$');
@@ -208,3 +447,172 @@ impl<'a> Resolver<'a> {
 		Ok(chain)
 	}
 }
+
+// --- functions ---
+/// (private) logs a variable expansion at `-vv`, without the value of a keyring secret
+fn log_expansion(name: &str, value: &str) {
+	let value = if name.starts_with("keyring.") { "********" } else { value };
+	verbose(2, format_args!("${{{name}}} -> {value}"));
+}
+
+/// (private) the built-in variables whose values depend on when they're expanded
+fn dynamic_variable(name: &str) -> Option<String> {
+	let now = Local::now();
+	match name {
+		"date" => Some(now.format("%Y-%m-%d").to_string()),
+		"time" => Some(now.format("%H-%M-%S").to_string()),
+		"datetime" => Some(now.format("%Y-%m-%d_%H-%M-%S").to_string()),
+		"rand" => Some(format!("{:08x}", random_u64() as u32)),
+		"uuid" => {
+			// random (version 4) uuid
+			let (hi, lo) = (random_u64(), random_u64());
+			let hi = (hi & !0xf000) | 0x4000;
+			let lo = (lo & !(0b11 << 62)) | (0b10 << 62);
+			Some(format!(
+				"{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+				hi >> 32, (hi >> 16) & 0xffff, hi & 0xffff, lo >> 48, lo & 0xffff_ffff_ffff
+			))
+		}
+		_ => None,
+	}
+}
+
+/// (private) a random number from the std hasher's random keys, good enough for names (not for secrets)
+fn random_u64() -> u64 {
+	let mut hasher = RandomState::new().build_hasher();
+	hasher.write_u128(Local::now().timestamp_nanos_opt().unwrap_or_default() as u128);
+	hasher.finish()
+}
+
+// --- tests ---
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::Config;
+	use crate::launcher::tests::setup;
+	use std::path::Path;
+
+	/// a launcher with only 'config' loaded (no apps)
+	fn launcher(config: Config) -> Launcher {
+		Launcher::init_config_only(Path::new("/nonexistent"), config)
+	}
+
+	/// an app from a toml snippet
+	fn app(toml: &str) -> App {
+		toml::from_str(toml).unwrap()
+	}
+
+	/// resolves the 'launch' command of the app called 'name'
+	fn resolve(l: &Launcher, name: &str) -> Result<ResolvedParts> {
+		let source = l.find_app(name).unwrap();
+		Resolver::new(l).resolve_command(source, &l.load_app_from(source).unwrap(), "launch")
+	}
+
+	#[test]
+	fn expand_app_masks_secrets() {
+		let l = launcher(Config::default());
+		let resolver = Resolver::new(&l);
+		let app = app("[vars]\ntoken = \"hunter2\"\n\n[cmds.launch]\nbin = \"game\"\nargs = [\"--token=$token\"]\n");
+		// what reading "hunter2" from the keyring would have recorded
+		resolver.secrets.borrow_mut().push("hunter2".into());
+
+		let expanded = resolver.expand_app(&app, &app).unwrap();
+		assert_eq!(expanded.cmds["launch"].args, ["--token=********"]);
+		assert_eq!(expanded.vars.unwrap()["token"], "********");
+	}
+
+	#[test]
+	fn expands_environment_variables() {
+		let l = launcher(Config::default());
+		let resolver = Resolver::new(&l);
+		let path = std::env::var("PATH").unwrap();
+
+		assert_eq!(resolver.expand(None, "$PATH/x").unwrap(), format!("{path}/x"));
+		assert_eq!(resolver.expand(None, "${PATH}x").unwrap(), format!("{path}x"));
+		assert_eq!(resolver.expand(None, "${env.PATH}").unwrap(), path);
+		assert!(resolver.take_undefined().is_empty());
+	}
+
+	#[test]
+	fn leaves_missing_variables_literal() {
+		let l = launcher(Config::default());
+		let resolver = Resolver::new(&l);
+
+		assert_eq!(resolver.expand(None, "$RAN_TEST_UNSET ${RAN_TEST_UNSET2}").unwrap(), "$RAN_TEST_UNSET ${RAN_TEST_UNSET2}");
+		assert_eq!(resolver.take_undefined(), ["RAN_TEST_UNSET", "RAN_TEST_UNSET2"]);
+	}
+
+	#[test]
+	fn escapes_dollar_signs() {
+		let l = launcher(Config::default());
+		let resolver = Resolver::new(&l);
+
+		assert_eq!(resolver.expand(None, "$$PATH costs $$5, $5 or $").unwrap(), "$PATH costs $5, $5 or $");
+	}
+
+	#[test]
+	fn own_variables_win_over_the_environment() {
+		let l = launcher(Config::default());
+		let resolver = Resolver::new(&l);
+		let app = app("[vars]\nPATH = \"/mine\"\n\n[cmds.launch]\nbin = \"game\"\n");
+
+		assert_eq!(resolver.expand(Some(&app), "$PATH").unwrap(), "/mine");
+		// unless the environment is asked for explicitly
+		assert_eq!(resolver.expand(Some(&app), "${env.PATH}").unwrap(), std::env::var("PATH").unwrap());
+	}
+
+	#[test]
+	fn detects_runner_cycles() {
+		let (_dir, l) = setup(Config::default(), &[
+			("a", "[cmds.launch]\nbin = \"@b\"\n"),
+			("b", "[cmds.launch]\nbin = \"@a\"\n"),
+			// a runner reached through one of its own other commands isn't a cycle
+			("c", "[cmds.launch]\nbin = \"@c setup\"\n\n[cmds.setup]\nbin = \"true\"\n"),
+		]);
+
+		let Err(error) = resolve(&l, "a") else {
+			panic!("resolved a runner chain that should have failed");
+		};
+		assert_eq!(error.to_string(), "infinite recursion in runner chain: a -> b -> a");
+		assert!(matches!(
+			error.downcast_ref::<LauncherError>(),
+			Some(LauncherError::CircularRunner(chain)) if chain == &["a", "b", "a"]
+		));
+		assert!(resolve(&l, "c").is_ok());
+	}
+
+	#[test]
+	fn limits_runner_depth() {
+		// app0 -> @app1 -> ... -> @app{MAX_RUNNER_DEPTH + 1}, all distinct so there's no cycle.
+		// app0 goes through one runner more than allowed
+		let apps: Vec<(String, String)> = (0..=MAX_RUNNER_DEPTH)
+			.map(|i| (format!("app{i}"), format!("[cmds.launch]\nbin = \"@app{}\"\n", i + 1)))
+			.chain([(format!("app{}", MAX_RUNNER_DEPTH + 1), "[cmds.launch]\nbin = \"true\"\n".into())])
+			.collect();
+		let apps: Vec<(&str, &str)> = apps.iter().map(|(name, toml)| (name.as_str(), toml.as_str())).collect();
+		let (_dir, l) = setup(Config::default(), &apps);
+
+		let Err(error) = resolve(&l, "app0") else {
+			panic!("resolved a runner chain that should have failed");
+		};
+		assert!(error.to_string().starts_with("runner chain is deeper than"), "{error}");
+		// one link shorter works
+		assert_eq!(resolve(&l, "app1").unwrap().chain.len(), MAX_RUNNER_DEPTH);
+	}
+
+	#[test]
+	fn working_dir_comes_from_the_runner_chain() {
+		let (_dir, l) = setup(Config::default(), &[
+			("outer", "[cmds.launch]\nbin = \"wine\"\nworking_dir = \"/opt/wine\"\n"),
+			("inner", "[cmds.launch]\nbin = \"@outer\"\n"),
+			("game", "[cmds.launch]\nbin = \"@inner\"\n"),
+			("own", "working_dir = \"/games/own\"\n\n[cmds.launch]\nbin = \"@inner\"\n"),
+		]);
+
+		// only the outermost runner sets one, so it's used
+		assert_eq!(resolve(&l, "game").unwrap().working_dir.as_deref(), Some("/opt/wine"));
+
+		// the app's own directory overrides the runner's
+		assert_eq!(resolve(&l, "own").unwrap().working_dir.as_deref(), Some("/games/own"));
+	}
+}
@@ -0,0 +1,117 @@
+// --- imports ---
+use anyhow::Result;
+use colored::*;
+use indexmap::IndexMap as Map;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+use ran_launcher::history::LaunchCounter;
+use ran_launcher::launcher::Launcher;
+use ran_launcher::resolver::Resolver;
+use ran_launcher::util::table::*;
+
+// --- constants ---
+/// how many of the most launched apps `ran stats` shows
+const TOP_LAUNCHED: usize = 5;
+
+// --- definitions ---
+/// an overview of the app library and launch history (see `ran stats`)
+#[derive(Serialize)]
+pub struct Stats {
+	pub apps: usize,
+	/// apps at least one alias (possibly through other aliases) leads to
+	pub aliased_apps: usize,
+	pub aliases: usize,
+	/// app count per top-level folder, "" for apps outside of any folder
+	pub folders: Map<String, usize>,
+	/// launches counted over all apps (see LaunchCounter)
+	pub launches: u64,
+	/// the most launched apps, most launched first
+	pub most_launched: Vec<LaunchCount>,
+}
+
+#[derive(Serialize)]
+pub struct LaunchCount {
+	pub app: String,
+	pub launches: u64,
+}
+
+// --- implementations ---
+impl Stats {
+	pub fn collect(l: &Launcher) -> Result<Self> {
+		let resolver = Resolver::new(l);
+		let aliases: Vec<&String> = l.config.alias.iter().flat_map(|a| a.keys()).collect();
+		let mut aliased: Vec<&String> = aliases.iter()
+			.filter_map(|alias| resolver.resolve_alias_chain(alias).ok())
+			.filter_map(|chain| match l.matching_apps(chain.last()?).as_slice() {
+				[source] => l.name_of(source),
+				_ => None,
+			})
+			.collect();
+		aliased.sort();
+		aliased.dedup();
+
+		let mut folders: Map<String, usize> = Map::new();
+		for name in l.apps.keys() {
+			let folder = match name.split_once('/') {
+				Some((folder, _)) => folder.to_string(),
+				None => String::new(),
+			};
+			*folders.entry(folder).or_default() += 1;
+		}
+		folders.sort_by(|a, _, b, _| a.cmp(b));
+
+		let counters = LaunchCounter::load_all(&l.config_path);
+		let launches = counters.values().map(|counter| counter.launch_count).sum();
+		let mut most_launched: Vec<LaunchCount> = counters.into_iter()
+			.map(|(app, counter)| LaunchCount { app, launches: counter.launch_count })
+			.collect();
+		most_launched.sort_by(|a, b| b.launches.cmp(&a.launches).then_with(|| a.app.cmp(&b.app)));
+		most_launched.truncate(TOP_LAUNCHED);
+
+		Ok(Self {
+			apps: l.apps.len(),
+			aliased_apps: aliased.len(),
+			aliases: aliases.len(),
+			folders,
+			launches,
+			most_launched,
+		})
+	}
+}
+
+impl Display for Stats {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let mut sections: Map<String, Map<String, String>> = Map::new();
+
+		// 1. library
+		let mut library = Map::new();
+		library.insert("Apps".bright_cyan().to_string(), self.apps.to_string());
+		library.insert("Aliases".bright_cyan().to_string(), self.aliases.to_string());
+		library.insert("Aliased Apps".bright_cyan().to_string(), self.aliased_apps.to_string());
+		sections.insert(format!("{}", "Library".bright_cyan().bold()), library);
+
+		// 2. folders
+		if !self.folders.is_empty() {
+			let mut folders = Map::new();
+			for (folder, count) in &self.folders {
+				let folder = if folder.is_empty() { "(no folder)".to_string() } else { format!("{folder}/") };
+				folders.insert(folder.yellow().to_string(), count.to_string());
+			}
+			sections.insert(format!("{}", "Folders".yellow().bold()), folders);
+		}
+
+		// 3. history
+		let mut history = Map::new();
+		if self.launches == 0 {
+			history.insert("(no launches recorded yet)".bright_magenta().to_string(), "".into());
+		} else {
+			history.insert("Launches".bright_magenta().to_string(), self.launches.to_string());
+			for LaunchCount { app, launches } in &self.most_launched {
+				history.insert(app.bright_magenta().to_string(), format!("{launches} launch(es)"));
+			}
+		}
+		sections.insert(format!("{}", "History".bright_magenta().bold()), history);
+
+		make_table(f, "Stats", generate_rows(sections))
+	}
+}
@@ -1,3 +1,8 @@
+// --- imports ---
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
 // --- functions ---
 /// puts child args in place of %! in parent args, or appends if no %! is found
 pub fn sandwich_args(parent: Vec<String>, child: Vec<String>) -> Vec<String> {
@@ -31,3 +36,30 @@ pub fn parse_bool(s: &str) -> Option<bool> {
 		_ => None,
 	}
 }
+
+/// parse a KEY=VALUE pair from cli arg (e.g. `--env PROTON_LOG=1`)
+pub fn parse_env_pair(s: &str) -> Result<(String, String), String> {
+	let (key, value) = s.split_once('=')
+		.ok_or_else(|| format!("expected KEY=VALUE, got '{s}'"))?;
+	let key = key.trim();
+	if key.is_empty() {
+		return Err(format!("empty key in '{s}'"));
+	}
+	Ok((key.to_string(), value.to_string()))
+}
+
+/// parses a json or inline-toml value (an array or a table) given on the command line,
+/// e.g. for `config set default_args '["--a"]'`
+pub fn parse_fragment<T: DeserializeOwned>(value: &str) -> Result<T> {
+	#[derive(Deserialize)]
+	struct Fragment<T> {
+		value: T,
+	}
+
+	if let Ok(parsed) = serde_json::from_str(value) {
+		return Ok(parsed);
+	}
+	let fragment: Fragment<T> = toml::from_str(&format!("value = {value}"))
+		.map_err(|e| anyhow!("{}", e.message()))?;
+	Ok(fragment.value)
+}
@@ -1,7 +1,10 @@
 // --- imports ---
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use indexmap::IndexMap as Map;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 use std::fs;
+use std::io::{ErrorKind, Write};
 use std::process::Command;
 
 // --- functions ---
@@ -11,6 +14,65 @@ pub fn default_config_path(name: &str) -> Result<PathBuf> {
 	Ok(base.join(name))
 }
 
+/// returns the user's preferred editor ($VISUAL, then $EDITOR), if set
+pub fn preferred_editor() -> Option<String> {
+	std::env::var("VISUAL").ok()
+		.or_else(|| std::env::var("EDITOR").ok())
+		.filter(|e| !e.trim().is_empty())
+}
+
+/// returns the terminal editor used when neither a preferred editor nor the system opener works
+pub fn fallback_editor() -> &'static str {
+	if cfg!(windows) { "notepad" } else { "nano" }
+}
+
+/// looks up 'program' in $PATH (respecting $PATHEXT on windows), or checks it directly if it's a path
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+	find_in_search_path(program, std::env::var_os("PATH").as_deref()?)
+}
+
+/// like 'find_in_path', but looks in 'search_path' (a PATH-style list of directories) instead of $PATH
+pub fn find_in_search_path(program: &str, search_path: &OsStr) -> Option<PathBuf> {
+	let path = Path::new(program);
+	if path.is_absolute() || path.components().count() > 1 {
+		return is_executable(path).then(|| path.to_path_buf());
+	}
+
+	let exts: Vec<String> = if cfg!(windows) {
+		std::env::var("PATHEXT")
+			.unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into())
+			.split(';')
+			.filter(|e| !e.is_empty())
+			.map(String::from)
+			.collect()
+	} else {
+		Vec::new()
+	};
+
+	std::env::split_paths(search_path).find_map(|dir| {
+		let candidate = dir.join(program);
+		if is_executable(&candidate) {
+			return Some(candidate);
+		}
+		exts.iter()
+			.map(|ext| dir.join(format!("{program}{ext}")))
+			.find(|p| is_executable(p))
+	})
+}
+
+/// (private) whether 'path' is a file we could execute
+fn is_executable(path: &Path) -> bool {
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+	}
+	#[cfg(not(unix))]
+	{
+		path.is_file()
+	}
+}
+
 /// opens 'path' in $VISUAL, $EDITOR, or OS-wide default program, or notepad/nano
 pub fn open_in_editor(path: &Path, create: bool) -> Result<()> {
 	if !path.exists() {
@@ -24,9 +86,7 @@ pub fn open_in_editor(path: &Path, create: bool) -> Result<()> {
 	}
 
 	// 1. try $VISUAL / $EDITOR
-	if let Some(editor) = std::env::var("VISUAL").ok()
-		.or_else(|| std::env::var("EDITOR").ok())
-	{
+	if let Some(editor) = preferred_editor() {
 		// try opening with the user’s editor
 		if Command::new(&editor).arg(path).status().is_ok() {
 			return Ok(());
@@ -39,14 +99,66 @@ pub fn open_in_editor(path: &Path, create: bool) -> Result<()> {
 	}
 
 	// 3. fallback terminal editors
-	#[cfg(windows)]
-	{
-		Command::new("notepad").arg(path).status()?;
+	Command::new(fallback_editor()).arg(path).status()?;
+
+	Ok(())
+}
+
+/// opens 'path' in an editor (see 'open_in_editor') while holding a '<path>.lock' file,
+/// refusing to open it if another edit holds the lock unless 'force' is set.
+/// note: the OS-wide default program usually returns right away, so the lock is only held for
+/// the whole editing session with $VISUAL/$EDITOR or the nano/notepad fallback.
+pub fn open_in_editor_locked(path: &Path, create: bool, force: bool) -> Result<()> {
+	if create && let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
 	}
-	#[cfg(not(windows))]
-	{
-		Command::new("nano").arg(path).status()?;
+
+	let mut lock_name = path.as_os_str().to_owned();
+	lock_name.push(".lock");
+	let lock = PathBuf::from(lock_name);
+
+	let mut options = fs::OpenOptions::new();
+	if force {
+		options.write(true).create(true).truncate(true);
+	} else {
+		options.write(true).create_new(true);
+	}
+	match options.open(&lock) {
+		Ok(mut file) => write!(file, "{}", std::process::id())?,
+		Err(e) if e.kind() == ErrorKind::AlreadyExists => bail!(
+			"'{}' is already being edited (lock file '{}' exists). use -f/--force to edit anyway.",
+			path.display(),
+			lock.display()
+		),
+		Err(e) => return Err(e.into()),
 	}
 
-	Ok(())
+	let result = open_in_editor(path, create);
+	let _ = fs::remove_file(&lock);
+	result
+}
+
+/// reads a simple env file (KEY=VALUE per line, blank lines and '#' comments are ignored)
+pub fn read_env_file(path: &Path) -> Result<Map<String, String>> {
+	let content = fs::read_to_string(path)
+		.with_context(|| format!("failed to read env file '{}'", path.display()))?;
+
+	let mut env = Map::new();
+	for (i, line) in content.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let (key, value) = line.split_once('=').ok_or_else(|| anyhow!(
+			"invalid line {} in env file '{}': expected KEY=VALUE",
+			i + 1,
+			path.display()
+		))?;
+		let key = key.trim();
+		if key.is_empty() {
+			bail!("empty key on line {} in env file '{}'", i + 1, path.display());
+		}
+		env.insert(key.to_string(), value.trim().to_string());
+	}
+	Ok(env)
 }
@@ -0,0 +1,26 @@
+// --- imports ---
+use colored::Colorize;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// --- constants ---
+/// how many times `-v/--verbose` was given (0 prints nothing, which is what library users get)
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+// --- functions ---
+pub fn set_verbosity(level: u8) {
+	VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+/// whether messages of 'level' (1 for `-v`, 2 for `-vv`) are printed
+pub fn enabled(level: u8) -> bool {
+	VERBOSITY.load(Ordering::Relaxed) >= level
+}
+
+/// prints 'message' to stderr if the verbosity is at least 'level'.
+/// pass `format_args!(...)` so nothing is formatted when it isn't printed.
+pub fn verbose(level: u8, message: impl Display) {
+	if enabled(level) {
+		eprintln!("{}", format!("[verbose] {message}").bright_black());
+	}
+}
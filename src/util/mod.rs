@@ -1,4 +1,7 @@
 // --- modules ---
 pub mod args;
 pub mod fs;
+pub mod log;
+pub mod search;
+pub mod signal;
 pub mod table;
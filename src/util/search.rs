@@ -0,0 +1,79 @@
+// --- functions ---
+/// scores how well 'candidate' matches 'query' (case-insensitive), lower is better.
+/// exact and leaf-name matches rank first, then leaf prefixes, substrings, and finally
+/// subsequences (e.g. "dm" matches "games/doom"). returns None if it doesn't match at all.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+	let query = query.trim().to_lowercase();
+	let candidate = candidate.to_lowercase();
+	let leaf = candidate.rsplit('/').next().unwrap_or(&candidate);
+
+	if candidate == query || leaf == query {
+		return Some(0);
+	}
+	if leaf.starts_with(&query) {
+		return Some(1000 + leaf.len());
+	}
+	if let Some(pos) = candidate.find(&query) {
+		return Some(2000 + pos);
+	}
+
+	// subsequence match, penalized by how spread out the matched characters are
+	let mut chars = candidate.char_indices();
+	let mut first = None;
+	let mut last = 0;
+	for q in query.chars() {
+		let (i, _) = chars.find(|(_, c)| *c == q)?;
+		first.get_or_insert(i);
+		last = i;
+	}
+	Some(3000 + last - first.unwrap_or(0))
+}
+
+/// computes the levenshtein (edit) distance between two strings
+pub fn levenshtein(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+	for (i, ca) in a.chars().enumerate() {
+		let mut cur = vec![i + 1; b.len() + 1];
+		for (j, cb) in b.iter().enumerate() {
+			let cost = if ca == *cb { 0 } else { 1 };
+			cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+		}
+		prev = cur;
+	}
+
+	prev[b.len()]
+}
+
+/// whether two app names look like they refer to the same thing, compared by leaf name
+/// (e.g. "games/doom" and "doom2", or "doom" and "other/doom")
+pub fn is_similar(a: &str, b: &str) -> bool {
+	let a = a.to_lowercase();
+	let b = b.to_lowercase();
+	let a = a.rsplit('/').next().unwrap_or(&a);
+	let b = b.rsplit('/').next().unwrap_or(&b);
+
+	let shorter = a.len().min(b.len());
+	(shorter >= 3 && (a.contains(b) || b.contains(a))) || levenshtein(a, b) <= shorter / 4
+}
+
+/// matches an app name against a glob pattern: '?' matches one character and '*' any run of
+/// characters within one path segment, while '**' also crosses '/' (e.g. "tests/**" matches "tests/a/b")
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+	fn matches(pattern: &[char], name: &[char]) -> bool {
+		match pattern {
+			[] => name.is_empty(),
+			['*', '*', rest @ ..] => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+			['*', rest @ ..] => (0..=name.len())
+				.take_while(|&i| i == 0 || name[i - 1] != '/')
+				.any(|i| matches(rest, &name[i..])),
+			['?', rest @ ..] => name.first().is_some_and(|&c| c != '/') && matches(rest, &name[1..]),
+			[c, rest @ ..] => name.first() == Some(c) && matches(rest, &name[1..]),
+		}
+	}
+
+	let pattern: Vec<char> = pattern.chars().collect();
+	let name: Vec<char> = name.chars().collect();
+	matches(&pattern, &name)
+}
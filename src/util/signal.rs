@@ -0,0 +1,151 @@
+// --- imports ---
+use std::process::{Child, Command};
+#[cfg(unix)]
+use std::sync::atomic::{AtomicI32, Ordering};
+
+// --- constants ---
+/// the signals forwarded to a foreground app
+#[cfg(unix)]
+const FORWARDED: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+/// the pid signals are forwarded to (negative for a whole process group), 0 if there's none
+#[cfg(unix)]
+static TARGET: AtomicI32 = AtomicI32::new(0);
+
+/// the last forwarded signal, 0 if there was none
+#[cfg(unix)]
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
+// --- definitions ---
+/// forwards SIGINT/SIGTERM/SIGHUP sent to ran to a foreground app while it's alive, so ran waits for
+/// the app to clean up instead of dying first. the previous handlers are restored on drop.
+/// does nothing on other platforms, where ctrl-c already reaches every process on the console.
+pub struct SignalForwarder {
+	#[cfg(unix)]
+	previous: Vec<(libc::c_int, libc::sighandler_t)>,
+}
+
+/// runs a foreground app in its own process group (so it can be killed along with everything it
+/// starts) while keeping it in charge of the terminal: if ran's group owns the terminal, the app's
+/// group is made its foreground group, otherwise reading from it would stop the app with SIGTTIN.
+/// ran takes the terminal back on drop. does nothing on other platforms.
+pub struct TerminalHandoff {
+	#[cfg(unix)]
+	owns_terminal: bool,
+}
+
+// --- implementations ---
+impl TerminalHandoff {
+	/// makes 'proc' start in a process group of its own, which takes over the terminal if ran has it
+	pub fn prepare(proc: &mut Command) -> Self {
+		#[cfg(unix)]
+		{
+			use std::os::unix::process::CommandExt;
+
+			// SAFETY: these calls only query the state of stdin and ran's own process group
+			let owns_terminal = unsafe {
+				libc::isatty(libc::STDIN_FILENO) == 1 && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp()
+			};
+			proc.process_group(0);
+			if owns_terminal {
+				// SAFETY: runs in the forked child before exec, and only makes async-signal-safe calls.
+				// a background group may only take the terminal with SIGTTOU ignored, which exec
+				// would keep, so its default is restored right after
+				unsafe {
+					proc.pre_exec(|| {
+						libc::setpgid(0, 0);
+						libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+						libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpid());
+						libc::signal(libc::SIGTTOU, libc::SIG_DFL);
+						Ok(())
+					});
+				}
+			}
+			Self { owns_terminal }
+		}
+		#[cfg(not(unix))]
+		{
+			let _ = proc;
+			Self {}
+		}
+	}
+}
+
+impl Drop for TerminalHandoff {
+	fn drop(&mut self) {
+		#[cfg(unix)]
+		if self.owns_terminal {
+			// SAFETY: ran is in the background group now, so SIGTTOU is ignored while taking the terminal back
+			unsafe {
+				let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+				libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+				libc::signal(libc::SIGTTOU, previous);
+			}
+		}
+	}
+}
+
+impl SignalForwarder {
+	pub fn install() -> Self {
+		#[cfg(unix)]
+		{
+			RECEIVED.store(0, Ordering::SeqCst);
+			let handler = forward as extern "C" fn(libc::c_int) as libc::sighandler_t;
+			let previous = FORWARDED.iter()
+				// SAFETY: the handler only touches atomics and calls kill(), which is async-signal-safe
+				.map(|&sig| (sig, unsafe { libc::signal(sig, handler) }))
+				.collect();
+			Self { previous }
+		}
+		#[cfg(not(unix))]
+		Self {}
+	}
+
+	/// forwards signals to 'child' from now on. 'own_group' means it leads its own process group,
+	/// which is then signalled as a whole. otherwise it shares ran's group and already gets ctrl-c
+	/// from the terminal, so SIGINT isn't sent twice.
+	pub fn watch(&self, child: &Child, own_group: bool) {
+		#[cfg(unix)]
+		{
+			let pid = child.id() as i32;
+			TARGET.store(if own_group { -pid } else { pid }, Ordering::SeqCst);
+		}
+		#[cfg(not(unix))]
+		let _ = (child, own_group);
+	}
+
+	/// the last signal ran received while forwarding, if any
+	pub fn received(&self) -> Option<i32> {
+		#[cfg(unix)]
+		{
+			Some(RECEIVED.load(Ordering::SeqCst)).filter(|&sig| sig != 0)
+		}
+		#[cfg(not(unix))]
+		None
+	}
+}
+
+impl Drop for SignalForwarder {
+	fn drop(&mut self) {
+		#[cfg(unix)]
+		{
+			TARGET.store(0, Ordering::SeqCst);
+			for &(sig, handler) in &self.previous {
+				// SAFETY: puts back the handler that was installed before
+				unsafe { libc::signal(sig, handler) };
+			}
+		}
+	}
+}
+
+// --- functions ---
+/// (private) the signal handler, records 'sig' and passes it on to the current target
+#[cfg(unix)]
+extern "C" fn forward(sig: libc::c_int) {
+	RECEIVED.store(sig, Ordering::SeqCst);
+	let target = TARGET.load(Ordering::SeqCst);
+	if target != 0 && (sig != libc::SIGINT || target < 0) {
+		// SAFETY: kill() is async-signal-safe
+		unsafe { libc::kill(target, sig) };
+	}
+}
@@ -1,5 +1,11 @@
+// --- constants ---
+/// terminals narrower than this get a plain list instead of boxed tables
+pub const MIN_TABLE_WIDTH: usize = 40;
+/// the escape code that turns colors and styles off again
+const ANSI_RESET: &str = "\x1b[0m";
+
 // --- imports ---
-use console::measure_text_width;
+use console::{measure_text_width, truncate_str, AnsiCodeIterator};
 use indexmap::IndexMap;
 use std::fmt::{self, Formatter};
 use terminal_size::{terminal_size, Height, Width};
@@ -8,20 +14,68 @@ use terminal_size::{terminal_size, Height, Width};
 /// wraps rows to fit terminal width
 #[allow(unused)]
 pub fn wrap_rows(rows: Vec<String>, term_w: usize) -> Vec<String> {
-	let mut wrapped = Vec::new();
+	rows.iter().flat_map(|row| split_at_width(row, term_w)).collect()
+}
 
-	for row in rows {
-		let mut start = 0;
-		let chars: Vec<char> = row.chars().collect(); // handle Unicode properly
-		while start < measure_text_width(&row) {
-			let end = (start + term_w).min(measure_text_width(&row));
-			let slice: String = chars[start..end].iter().collect();
-			wrapped.push(slice);
-			start += term_w;
+/// (private) hard-splits 's' into pieces at most 'width' columns wide. escape codes don't count
+/// towards the width, and colors active at a split are closed and opened again in the next piece
+fn split_at_width(s: &str, width: usize) -> Vec<String> {
+	let mut pieces = Vec::new();
+	let mut piece = String::new();
+	let mut piece_width = 0;
+	// escape codes since the last reset
+	let mut active = String::new();
+
+	for (part, is_ansi) in AnsiCodeIterator::new(s) {
+		if is_ansi {
+			match part {
+				ANSI_RESET => active.clear(),
+				_ => active.push_str(part),
+			}
+			piece.push_str(part);
+			continue;
+		}
+		for c in part.chars() {
+			let char_width = measure_text_width(c.encode_utf8(&mut [0; 4]));
+			if piece_width > 0 && piece_width + char_width > width {
+				if !active.is_empty() {
+					piece.push_str(ANSI_RESET);
+				}
+				pieces.push(std::mem::replace(&mut piece, active.clone()));
+				piece_width = 0;
+			}
+			piece.push(c);
+			piece_width += char_width;
 		}
 	}
+	// trailing escape codes alone only end up in a piece of their own after a split, where they aren't needed
+	if piece_width > 0 || pieces.is_empty() {
+		pieces.push(piece);
+	}
+	pieces
+}
 
-	wrapped
+/// (private) makes every line of a wrapped value close the colors that are still active at its end
+/// and open them again at the start of the next line, so they don't spill into the table borders
+fn carry_colors(lines: Vec<String>) -> Vec<String> {
+	let mut active = String::new();
+	lines.into_iter()
+		.map(|line| {
+			let mut carried = active.clone();
+			carried.push_str(&line);
+			for (part, is_ansi) in AnsiCodeIterator::new(&line) {
+				match (part, is_ansi) {
+					(ANSI_RESET, true) => active.clear(),
+					(_, true) => active.push_str(part),
+					_ => {}
+				}
+			}
+			if !active.is_empty() {
+				carried.push_str(ANSI_RESET);
+			}
+			carried
+		})
+		.collect()
 }
 
 /// returns width of the current terminal, or 80 as a fallback if it can't be detected
@@ -34,8 +88,15 @@ pub fn get_term_width() -> usize {
 }
 
 /// creates a table with the given name and rows, fitting to terminal width.
+/// on terminals narrower than MIN_TABLE_WIDTH, the rows are written as a plain list without borders.
 pub fn make_table(f: &mut Formatter<'_>, name: &str, rows: Vec<String>) -> fmt::Result {
-	let _term_w = get_term_width();
+	if get_term_width() < MIN_TABLE_WIDTH {
+		writeln!(f, "{name}")?;
+		for row in rows {
+			writeln!(f, "{row}")?;
+		}
+		return Ok(());
+	}
 	// wrap rows to fit terminal width minus borders
 	// let rows = wrap_rows(rows, term_w - 4);
 
@@ -73,13 +134,24 @@ pub fn make_table(f: &mut Formatter<'_>, name: &str, rows: Vec<String>) -> fmt::
 	Ok(())
 }
 
-/// generates rows for the config/app info display, handling ANSI color codes and wrapping values to fit terminal width (though currently ANSI breaks on line-wraps).
+/// generates rows for the config/app info display, handling ANSI color codes and wrapping values to fit terminal width.
 /// expects sections in the format of section name → (key → value).
 pub fn generate_rows(
 	sections: IndexMap<String, IndexMap<String, String>>,
 ) -> Vec<String> {
-	let term_w = get_term_width().saturating_sub(4); // borders handled in make_box
+	// borders handled in make_box. plain lists (see make_table) aren't wrapped, the terminal does that
+	let term_w = match get_term_width() {
+		w if w < MIN_TABLE_WIDTH => usize::MAX,
+		w => w.saturating_sub(4),
+	};
+	generate_rows_for_width(sections, term_w)
+}
 
+/// (private) generate_rows for rows at most 'term_w' columns wide
+fn generate_rows_for_width(
+	sections: IndexMap<String, IndexMap<String, String>>,
+	term_w: usize,
+) -> Vec<String> {
 	// find longest key length
 	let (longest_key_length, _) = sections
 		.iter()
@@ -107,43 +179,18 @@ pub fn generate_rows(
 				continue;
 			}
 
-			let words: Vec<&str> = v.split_whitespace().collect();
+			// the value's lines, wrapped at spaces (or hard-split if a word doesn't fit a line)
+			let mut lines: Vec<String> = Vec::new();
 			let mut current_line = String::new();
-			let mut first_line = true;
-
-			for word in words {
+			for word in v.split_whitespace() {
 				let word_len = measure_text_width(word);
 
 				// word longer than line → hard split
 				if word_len > value_max_width {
 					if !current_line.is_empty() {
-						let line = if first_line {
-							format!("  {}{} : {}", k, key_pad, current_line)
-						} else {
-							format!("{}{}", indent, current_line)
-						};
-						rows.push(line.chars().take(term_w).collect());
-						current_line.clear();
-						first_line = false;
-					}
-
-					let chars: Vec<char> = word.chars().collect();
-					let mut start = 0;
-					while start < chars.len() {
-						let end = (start + value_max_width).min(chars.len());
-						let slice: String = chars[start..end].iter().collect();
-
-						let line = if first_line {
-							format!("  {}{} : {}", k, key_pad, slice)
-						} else {
-							format!("{}{}", indent, slice)
-						};
-						rows.push(line.chars().take(term_w).collect());
-
-						start = end;
-						first_line = false;
+						lines.push(std::mem::take(&mut current_line));
 					}
-
+					lines.extend(split_at_width(word, value_max_width));
 					continue;
 				}
 
@@ -152,17 +199,8 @@ pub fn generate_rows(
 				} else {
 					measure_text_width(&current_line) + 1 + word_len
 				};
-
 				if new_len > value_max_width {
-					let line = if first_line {
-						format!("  {}{} : {}", k, key_pad, current_line)
-					} else {
-						format!("{}{}", indent, current_line)
-					};
-					rows.push(line.chars().take(term_w).collect());
-
-					current_line.clear();
-					first_line = false;
+					lines.push(std::mem::take(&mut current_line));
 				}
 
 				if !current_line.is_empty() {
@@ -170,15 +208,17 @@ pub fn generate_rows(
 				}
 				current_line.push_str(word);
 			}
-
 			// flush remainder
 			if !current_line.is_empty() {
-				let line = if first_line {
-					format!("  {}{} : {}", k, key_pad, current_line)
-				} else {
-					format!("{}{}", indent, current_line)
+				lines.push(current_line);
+			}
+
+			for (i, line) in carry_colors(lines).into_iter().enumerate() {
+				let line = match i {
+					0 => format!("  {}{} : {}", k, key_pad, line),
+					_ => format!("{}{}", indent, line),
 				};
-				rows.push(line.chars().take(term_w).collect());
+				rows.push(truncate_str(&line, term_w, "").into_owned());
 			}
 		}
 
@@ -188,3 +228,70 @@ pub fn generate_rows(
 
 	rows
 }
+
+// --- tests ---
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use console::strip_ansi_codes;
+	use std::fmt::Display;
+
+	/// 'text' in yellow, the way `colored` writes it
+	fn yellow(text: &str) -> String {
+		format!("\x1b[33m{text}{ANSI_RESET}")
+	}
+
+	/// whether every color opened in 'row' is closed again by its end
+	fn colors_closed(row: &str) -> bool {
+		AnsiCodeIterator::new(row)
+			.filter(|(_, is_ansi)| *is_ansi)
+			.last()
+			.is_none_or(|(code, _)| code == ANSI_RESET)
+	}
+
+	fn sections() -> IndexMap<String, IndexMap<String, String>> {
+		let mut general = IndexMap::new();
+		general.insert(yellow("Terminal Runner"), yellow("kitty --single-instance --title ran-launcher --hold"));
+		general.insert(yellow("Path"), yellow(&"/very/long/path".repeat(6)));
+		general.insert("Plain".into(), "short".into());
+		IndexMap::from([(yellow("General"), general)])
+	}
+
+	#[test]
+	fn split_at_width_keeps_colors_intact() {
+		let word = yellow("abcdefghij");
+		let pieces = split_at_width(&word, 4);
+		assert_eq!(pieces.iter().map(|p| strip_ansi_codes(p)).collect::<Vec<_>>(), ["abcd", "efgh", "ij"]);
+		for piece in &pieces {
+			assert!(piece.starts_with("\x1b[33m"), "{piece:?}");
+			assert!(colors_closed(piece), "{piece:?}");
+		}
+	}
+
+	#[test]
+	fn colored_rows_fit_the_width() {
+		let rows = generate_rows_for_width(sections(), 36);
+		for row in &rows {
+			assert!(measure_text_width(row) <= 36, "{row:?}");
+			assert!(colors_closed(row), "{row:?}");
+		}
+		// nothing is lost when wrapping
+		let text: String = rows.iter().map(|row| strip_ansi_codes(row).replace(' ', "")).collect();
+		assert!(text.contains(&"/very/long/path".repeat(6)));
+		assert!(text.contains("kitty--single-instance--titleran-launcher--hold"));
+	}
+
+	#[test]
+	fn table_borders_line_up_with_colored_rows() {
+		struct Table(Vec<String>);
+		impl Display for Table {
+			fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+				make_table(f, "Config Info", self.0.clone())
+			}
+		}
+
+		let table = Table(generate_rows_for_width(sections(), 36)).to_string();
+		let widths: Vec<usize> = table.lines().map(measure_text_width).collect();
+		assert!(widths.iter().all(|&w| w == widths[0]), "{table}");
+	}
+}
@@ -0,0 +1,25 @@
+// the default config path depends on HOME and XDG_CONFIG_HOME, which are changed here. this is the
+// only test in this binary, so nothing else reads the environment while it does.
+
+#[cfg(target_os = "linux")]
+#[test]
+fn default_config_path_follows_xdg() {
+	use ran_launcher::util::fs::default_config_path;
+	use std::path::Path;
+
+	// SAFETY: no other threads are running (see above)
+	unsafe {
+		std::env::set_var("HOME", "/home/tester");
+		std::env::remove_var("XDG_CONFIG_HOME");
+	}
+	assert_eq!(default_config_path("ran").unwrap(), Path::new("/home/tester/.config/ran"));
+
+	// SAFETY: as above
+	unsafe { std::env::set_var("XDG_CONFIG_HOME", "/tmp/xdg") };
+	assert_eq!(default_config_path("ran").unwrap(), Path::new("/tmp/xdg/ran"));
+
+	// a relative XDG_CONFIG_HOME is invalid and ignored
+	// SAFETY: as above
+	unsafe { std::env::set_var("XDG_CONFIG_HOME", "relative") };
+	assert_eq!(default_config_path("ran").unwrap(), Path::new("/home/tester/.config/ran"));
+}